@@ -0,0 +1,501 @@
+//! Embeds the MASM files under `../masm` into the crate and assembles them, so that a MASM
+//! syntax error fails the build instead of a binary at runtime.
+//!
+//! - every `accounts/*.toml` manifest is assembled into a contract library, with typed storage
+//!   accessors and Rust bindings generated from its slots and procedure signatures,
+//! - every `scripts/*.masm` transaction script and `notes/*.masm` note script is assembled into
+//!   a program linked against all contract libraries.
+//!
+//! The serialized artifacts are written to `OUT_DIR` together with `masm_registry.rs`, which
+//! `src/common/registry.rs` includes.
+
+use miden_assembly::{
+    ast::{Module, ModuleKind},
+    utils::Serializable,
+    Assembler, Library, LibraryPath,
+};
+use miden_lib::transaction::TransactionKernel;
+use serde::Deserialize;
+
+use std::{
+    collections::BTreeSet,
+    env,
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Namespace of contract libraries without an explicit `library` in their manifest, keep in
+/// sync with `EXTERNAL_CONTRACT_NAMESPACE`.
+const EXTERNAL_CONTRACT_NAMESPACE: &str = "external_contract";
+
+/// The manifest fields needed to assemble the contract library and generate its typed storage
+/// accessors and procedure bindings.
+#[derive(Deserialize)]
+struct Manifest {
+    name: String,
+    source: PathBuf,
+    library: Option<String>,
+    #[serde(default)]
+    storage: Vec<Slot>,
+    #[serde(default)]
+    procedures: Vec<Procedure>,
+}
+
+#[derive(Deserialize)]
+struct Slot {
+    name: String,
+    #[serde(rename = "type")]
+    kind: String,
+    value_type: Option<String>,
+    key_type: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Procedure {
+    name: String,
+    description: Option<String>,
+    #[serde(default)]
+    context: Option<String>,
+    #[serde(default)]
+    inputs: Vec<Param>,
+    #[serde(default)]
+    outputs: Vec<Param>,
+    #[serde(default)]
+    advice: Vec<Param>,
+}
+
+#[derive(Deserialize)]
+struct Param {
+    name: String,
+    #[serde(rename = "type")]
+    param_type: String,
+}
+
+fn main() {
+    let masm_dir = Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap()).join("../masm");
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    println!("cargo:rerun-if-changed={}", masm_dir.display());
+
+    let mut registry = String::from("// @generated by build.rs, do not edit.\n");
+
+    // Contract libraries
+    let mut libraries = Vec::new();
+    let mut sources = BTreeSet::new();
+    let mut consts = String::new();
+    for manifest_path in files_with_extension(&masm_dir.join("accounts"), "toml") {
+        let manifest: Manifest = toml::from_str(&read(&manifest_path))
+            .unwrap_or_else(|err| panic!("invalid manifest {}: {err}", manifest_path.display()));
+        let source_path = manifest_path.parent().unwrap().join(&manifest.source);
+        let library_path = manifest.library.clone().unwrap_or_else(|| {
+            format!("{EXTERNAL_CONTRACT_NAMESPACE}::{}_contract", manifest.name)
+        });
+        let library = assemble_library(&library_path, &source_path);
+        check_procedures(&manifest, &library, &manifest_path);
+        let library_file = out_dir.join(format!("{}.masl", manifest.name));
+        fs::write(&library_file, library.to_bytes()).unwrap();
+        sources.insert(fs::canonicalize(&source_path).unwrap());
+        libraries.push(library);
+
+        writeln!(
+            consts,
+            "    /// `masm/accounts/{}`\n    pub const {}: ContractArtifact = ContractArtifact {{ \
+             name: {:?}, library_path: {:?}, manifest_toml: include_str!({:?}), \
+             source: include_str!({:?}), library_bytes: include_bytes!({:?}) }};",
+            manifest_path.file_name().unwrap().to_string_lossy(),
+            const_name(&manifest.name),
+            manifest.name,
+            library_path,
+            manifest_path,
+            source_path,
+            library_file,
+        )
+        .unwrap();
+        consts.push_str(&contract_module(&manifest));
+    }
+    for source_path in files_with_extension(&masm_dir.join("accounts"), "masm") {
+        if !sources.contains(&fs::canonicalize(&source_path).unwrap()) {
+            panic!(
+                "{} is not referenced by any manifest",
+                source_path.display()
+            );
+        }
+    }
+    write_module(&mut registry, "accounts", "ContractArtifact", &consts);
+
+    // Transaction and note scripts
+    for (dir, artifact) in [
+        ("scripts", "TxScriptArtifact"),
+        ("notes", "NoteScriptArtifact"),
+    ] {
+        let mut consts = String::new();
+        for source_path in files_with_extension(&masm_dir.join(dir), "masm") {
+            let name = source_path
+                .file_stem()
+                .unwrap()
+                .to_string_lossy()
+                .to_string();
+            let program_file = out_dir.join(format!("{dir}-{name}.masb"));
+            fs::write(&program_file, assemble_program(&source_path, &libraries)).unwrap();
+
+            writeln!(
+                consts,
+                "    /// `masm/{dir}/{name}.masm`\n    pub const {}: {artifact} = {artifact} {{ \
+                 name: {name:?}, source: include_str!({source_path:?}), \
+                 program_bytes: include_bytes!({program_file:?}) }};",
+                const_name(&name),
+            )
+            .unwrap();
+        }
+        write_module(&mut registry, dir, artifact, &consts);
+    }
+
+    fs::write(out_dir.join("masm_registry.rs"), registry).unwrap();
+}
+
+fn assembler() -> Assembler {
+    TransactionKernel::assembler().with_debug_mode(true)
+}
+
+fn assemble_library(library_path: &str, source_path: &Path) -> Library {
+    let assembler = assembler();
+    let path = LibraryPath::new(library_path)
+        .unwrap_or_else(|err| panic!("invalid library path `{library_path}`: {err}"));
+    let module = Module::parser(ModuleKind::Library)
+        .parse_str(path, read(source_path), &assembler.source_manager())
+        .and_then(|module| assembler.assemble_library([module]));
+
+    module.unwrap_or_else(|report| fail(source_path, report))
+}
+
+/// Assembles the program at `source_path` and returns it serialized.
+fn assemble_program(source_path: &Path, libraries: &[Library]) -> Vec<u8> {
+    libraries
+        .iter()
+        .try_fold(assembler(), |assembler, library| {
+            assembler.with_library(library)
+        })
+        .and_then(|assembler| assembler.assemble_program(read(source_path)))
+        .map(|program| program.to_bytes())
+        .unwrap_or_else(|report| fail(source_path, report))
+}
+
+fn fail(source_path: &Path, report: miden_assembly::diagnostics::Report) -> ! {
+    panic!("failed to assemble {}:\n{report:?}", source_path.display())
+}
+
+/// Checks that every exported procedure has exactly one signature in the manifest, and that
+/// every signature is of an exported procedure.
+fn check_procedures(manifest: &Manifest, library: &Library, manifest_path: &Path) {
+    let exports: BTreeSet<&str> = library
+        .exports()
+        .map(|export| export.name.as_str())
+        .collect();
+    let mut declared = BTreeSet::new();
+    for procedure in &manifest.procedures {
+        if !exports.contains(procedure.name.as_str()) {
+            panic!(
+                "{}: procedure `{}` is not exported by {}",
+                manifest_path.display(),
+                procedure.name,
+                manifest.source.display()
+            );
+        }
+        if !declared.insert(procedure.name.as_str()) {
+            panic!(
+                "{}: procedure `{}` is declared twice",
+                manifest_path.display(),
+                procedure.name
+            );
+        }
+    }
+    if let Some(missing) = exports.difference(&declared).next() {
+        panic!(
+            "{}: exported procedure `{missing}` has no `[[procedures]]` signature",
+            manifest_path.display()
+        );
+    }
+}
+
+/// Generates `accounts::<name>`, with `Storage` holding one typed accessor per storage slot and
+/// `<Name>Contract` holding one binding per procedure callable from a transaction.
+fn contract_module(manifest: &Manifest) -> String {
+    format!(
+        "\n    /// Typed storage and procedure bindings of the `{name}` contract.\n    \
+         pub mod {module} {{\n        \
+         use crate::common::{{component_storage_offset, ManifestError, StorageError, StorageSchema}};\n        \
+         use miden_client::accounts::{{Account, AccountId}};\n\n{storage}\n{bindings}    }}\n",
+        name = manifest.name,
+        module = const_name(&manifest.name).to_lowercase(),
+        storage = storage_struct(manifest),
+        bindings = bindings_struct(manifest),
+    )
+}
+
+/// Generates `Storage`, with one typed accessor per storage slot.
+fn storage_struct(manifest: &Manifest) -> String {
+    let mut accessors = String::new();
+    for slot in &manifest.storage {
+        if !is_identifier(&slot.name) {
+            panic!(
+                "storage slot `{}` of `{}` is not a valid Rust identifier",
+                slot.name, manifest.name
+            );
+        }
+        let value_type = rust_type(slot.value_type.as_deref());
+        match slot.kind.as_str() {
+            "value" => writeln!(
+                accessors,
+                "        /// Value slot `{0}`.\n        pub fn {0}(&self) -> Result<{1}, StorageError> \
+                 {{ self.schema.view_at(self.account.storage(), self.offset).get(\"{0}\") }}",
+                slot.name, value_type
+            ),
+            _ => writeln!(
+                accessors,
+                "        /// Entry at `key` of map slot `{0}`.\n        pub fn {0}(&self, key: {2}) -> \
+                 Result<{1}, StorageError> {{ self.schema.view_at(self.account.storage(), self.offset).get_map(\"{0}\", key) }}",
+                slot.name,
+                value_type,
+                rust_type(slot.key_type.as_deref())
+            ),
+        }
+        .unwrap();
+    }
+
+    format!(
+        "        /// Reads the storage of an account holding the `{name}` contract by slot name.\n        \
+         pub struct Storage<'a> {{ schema: StorageSchema, account: &'a Account, offset: u8 }}\n\n        \
+         impl<'a> Storage<'a> {{\n        \
+         /// Reads `account`, whose first component is the `{name}` contract.\n        \
+         pub fn new(account: &'a Account) -> Result<Self, ManifestError> {{ \
+         Ok(Self {{ schema: super::{konst}.manifest()?.schema()?, account, \
+         offset: component_storage_offset(account.account_type(), 0) }}) }}\n\n\
+         {accessors}        }}\n",
+        name = manifest.name,
+        konst = const_name(&manifest.name),
+    )
+}
+
+/// Generates `<Name>Contract`, with for every procedure callable from a transaction a
+/// `<procedure>_call` function building the [`ProcedureCall`] and a `<procedure>` method
+/// executing and submitting it.
+fn bindings_struct(manifest: &Manifest) -> String {
+    let konst = const_name(&manifest.name);
+    let mut methods = String::new();
+    for procedure in &manifest.procedures {
+        let context = procedure.context.as_deref().unwrap_or("transaction");
+        match context {
+            "transaction" => {}
+            "note" => continue,
+            other => panic!(
+                "unknown context `{other}` of procedure `{}` of `{}`, expected transaction or note",
+                procedure.name, manifest.name
+            ),
+        }
+        if !is_identifier(&procedure.name)
+            || ["new", "account_id"].contains(&procedure.name.as_str())
+        {
+            panic!(
+                "procedure `{}` of `{}` cannot be a binding method name",
+                procedure.name, manifest.name
+            );
+        }
+
+        let mut names = BTreeSet::new();
+        let mut params = Vec::new();
+        let mut builder = String::new();
+        for input in &procedure.inputs {
+            let (rust_type, arg) = input_type(&input.param_type, &input.name);
+            params.push(format!("{}: {rust_type}", input.name));
+            builder.push_str(&format!(".arg({arg})"));
+            names.insert(input.name.as_str());
+        }
+        if !procedure.advice.is_empty() {
+            let mut advice = String::from("crate::common::AdviceBuilder::new()");
+            for value in &procedure.advice {
+                let rust_type = match value.param_type.as_str() {
+                    "u64" => "u64",
+                    "word" => "miden_client::Word",
+                    "bytes" => "&[u8]",
+                    other => panic!(
+                        "unknown advice type `{other}` of procedure `{}`, expected u64, word or \
+                         bytes",
+                        procedure.name
+                    ),
+                };
+                params.push(format!("{}: {rust_type}", value.name));
+                advice.push_str(&format!(
+                    ".{}({:?}, {})",
+                    value.param_type, value.name, value.name
+                ));
+                names.insert(value.name.as_str());
+            }
+            builder.push_str(&format!(".advice(&{advice}.build()?)"));
+        }
+        let param_names: Vec<&str> = procedure
+            .inputs
+            .iter()
+            .chain(&procedure.advice)
+            .map(|param| param.name.as_str())
+            .collect();
+        if names.len() != param_names.len()
+            || param_names
+                .iter()
+                .any(|name| !is_identifier(name) || *name == "client")
+        {
+            panic!(
+                "parameters of procedure `{}` of `{}` must be distinct Rust identifiers other \
+                 than `client`",
+                procedure.name, manifest.name
+            );
+        }
+        for output in &procedure.outputs {
+            input_type(&output.param_type, &output.name);
+        }
+
+        let stack = |params: &[Param]| {
+            params
+                .iter()
+                .map(|param| param.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let description = procedure
+            .description
+            .as_deref()
+            .map(|description| {
+                // Every line of a multi-line TOML string becomes its own doc comment line
+                let mut doc: String = description
+                    .trim_end()
+                    .lines()
+                    .map(|line| format!("        /// {line}\n").replace("/// \n", "///\n"))
+                    .collect();
+                doc.push_str("        ///\n");
+                doc
+            })
+            .unwrap_or_default();
+        let signature = format!(
+            "        /// Inputs: `[{}]`, outputs: `[{}]`, advice: `[{}]`.\n",
+            stack(&procedure.inputs),
+            stack(&procedure.outputs),
+            stack(&procedure.advice),
+        );
+        writeln!(
+            methods,
+            "{description}{signature}        \
+             pub fn {name}_call({params}) -> Result<crate::common::ProcedureCall, crate::common::CallError> {{ \
+             {body} }}\n\n\
+             {description}{signature}        \
+             ///\n        /// Executes the procedure against the contract account and submits the transaction.\n        \
+             pub async fn {name}(&self, client: &mut miden_client::Client<miden_client::crypto::RpoRandomCoin>{comma}{params}) -> \
+             Result<crate::common::SubmitOutcome, crate::common::CallError> {{ \
+             Self::{name}_call({args})?.submit(client, self.account_id).await }}\n",
+            name = procedure.name,
+            body = if builder.is_empty() {
+                format!("super::{konst}.call({:?})", procedure.name)
+            } else {
+                format!("Ok(super::{konst}.call({:?})?{builder})", procedure.name)
+            },
+            params = params.join(", "),
+            comma = if params.is_empty() { "" } else { ", " },
+            args = param_names.join(", "),
+        )
+        .unwrap();
+    }
+
+    format!(
+        "        /// Calls the procedures of an account holding the `{name}` contract.\n        \
+         #[derive(Clone, Copy, Debug)]\n        \
+         pub struct {pascal}Contract {{ account_id: AccountId }}\n\n        \
+         impl {pascal}Contract {{\n        \
+         pub fn new(account_id: AccountId) -> Self {{ Self {{ account_id }} }}\n\n        \
+         /// Returns the ID of the contract account.\n        \
+         pub fn account_id(&self) -> AccountId {{ self.account_id }}\n\n\
+         {methods}        }}\n",
+        name = manifest.name,
+        pascal = pascal_case(&manifest.name),
+    )
+}
+
+/// Rust type of a procedure input type and the expression converting `name` to a `StackArg`.
+fn input_type(param_type: &str, name: &str) -> (&'static str, String) {
+    match param_type {
+        "felt" => ("miden_client::Felt", name.to_string()),
+        "u64" => ("u64", format!("miden_client::Felt::new({name})")),
+        "word" => ("miden_client::Word", name.to_string()),
+        "account-id" => ("AccountId", name.to_string()),
+        "asset" => ("miden_objects::assets::Asset", name.to_string()),
+        other => panic!(
+            "unknown stack type `{other}` of `{name}`, expected felt, u64, word, account-id or \
+             asset"
+        ),
+    }
+}
+
+/// Rust type of a manifest slot type, keep in sync with `SlotType`.
+fn rust_type(slot_type: Option<&str>) -> &'static str {
+    match slot_type.unwrap_or("word") {
+        "word" => "miden_client::Word",
+        "felt" => "miden_client::Felt",
+        "u64" => "u64",
+        "account-id" => "miden_client::accounts::AccountId",
+        other => panic!("unknown slot type `{other}`"),
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_lowercase() || first == '_')
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+fn pascal_case(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            let first = chars.next().unwrap().to_ascii_uppercase();
+            std::iter::once(first).chain(chars).collect::<String>()
+        })
+        .collect()
+}
+
+fn write_module(registry: &mut String, name: &str, artifact: &str, consts: &str) {
+    // An empty directory gives an empty module, without an unused import
+    let import = if consts.is_empty() {
+        String::new()
+    } else {
+        format!("    use super::{artifact};\n\n")
+    };
+    writeln!(registry, "\npub mod {name} {{\n{import}{consts}}}").unwrap();
+}
+
+fn files_with_extension(dir: &Path, extension: &str) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .unwrap_or_else(|err| panic!("failed to read {}: {err}", dir.display()))
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == extension))
+        .collect();
+    files.sort();
+    files
+}
+
+fn read(path: &Path) -> String {
+    println!("cargo:rerun-if-changed={}", path.display());
+    fs::read_to_string(path)
+        .unwrap_or_else(|err| panic!("failed to read {}: {err}", path.display()))
+}
+
+fn const_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
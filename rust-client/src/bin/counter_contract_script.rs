@@ -1,44 +1,23 @@
-use miden_client::{
-    accounts::AccountData,
-    transactions::{TransactionKernel, TransactionRequest},
-    ClientError, Word,
-};
-use miden_objects::accounts::AccountComponent;
-use miden_objects::accounts::StorageSlot;
-use miden_objects::assembly::Assembler;
-use miden_objects::crypto::hash::rpo::RpoDigest;
-
-use rust_client::common::{create_new_account, initialize_client};
-use std::fs;
-use std::path::Path;
+use miden_client::accounts::AccountData;
 
-use tokio::time::Duration;
+use rust_client::common::{
+    accounts, dry_run, dry_run_requested, initialize_client_with_authenticator, wait_for_commit,
+    KeyGeneration, DEFAULT_COMMIT_TIMEOUT,
+};
 
 #[tokio::main]
-async fn main() -> Result<(), ClientError> {
-    let mut client = initialize_client().await?;
+async fn main() -> Result<(), String> {
+    let (mut client, authenticator) = initialize_client_with_authenticator().await?;
 
     //------------------------------------------------------------
     // STEP 1: Create a basic counter contract
     //------------------------------------------------------------
     println!("\n[STEP 1] Creating Counter Contract.");
 
-    // Initializing Account
-    let file_path = Path::new("../masm/accounts/counter.masm");
-
-    // Read the file contents
-    let account_code = fs::read_to_string(file_path).expect("Failed to read the file");
-
-    let assembler: Assembler = TransactionKernel::assembler().with_debug_mode(true);
-    let account_component = AccountComponent::compile(
-        account_code,
-        assembler,
-        vec![StorageSlot::Value(Word::default())],
-    )
-    .unwrap()
-    .with_supports_all_types();
-
-    let (counter_contract, counter_seed, auth_secret_key) = create_new_account(account_component);
+    // Initializing Account from the contract compiled at build time
+    let contract = accounts::COUNTER;
+    let (counter_contract, counter_seed, auth_secret_key) =
+        contract.create_account(KeyGeneration::Random)?;
 
     println!("counter_contract hash: {:?}", counter_contract.hash());
     println!(
@@ -58,55 +37,34 @@ async fn main() -> Result<(), ClientError> {
         .await
         .unwrap();
 
-    // procedure roots
-    let procedures = counter_contract.code().procedure_roots();
-    let procedures_vec: Vec<RpoDigest> = procedures.collect();
-    for (index, procedure) in procedures_vec.iter().enumerate() {
-        println!("Procedure {}: {:?}", index + 1, procedure.to_hex());
+    // procedure roots, by exported name
+    let procedures = contract.procedure_roots();
+    for (name, root) in procedures.iter() {
+        println!("Procedure {}: {:?}", name, root.to_hex());
     }
 
-    println!("number of procedures: {}", procedures_vec.len());
-
     //------------------------------------------------------------
-    // STEP 2: Call Counter Contract with script
+    // STEP 2: Call Counter Contract through its generated binding
     //------------------------------------------------------------
-    println!("\n[STEP 2] Call Counter Contract With Script");
-
-    // --- 1) Grab the procedure #2 hash and prepare it for insertion into the script
-    let procedure_2_hash = procedures_vec[0].to_hex();
-    let procedure_call = format!("{}", procedure_2_hash);
-
-    // --- 2) Load MASM script
-    let file_path = Path::new("../masm/scripts/counter_script.masm");
-    let original_code = fs::read_to_string(file_path).expect("Failed to read the file");
-
-    // --- 3) Replace {increment_count} in the script with the actual call line
-    let replaced_code = original_code.replace("{increment_count}", &procedure_call);
-    println!("Final script:\n{}", replaced_code);
-
-    // --- 4) Compile the script (now containing the procedure #2 hash)
-    let tx_script = client.compile_tx_script(vec![], &replaced_code).unwrap();
-
-    // --- 5) Execute the transaction
-    let tx_increment_request = TransactionRequest::new()
-        .with_custom_script(tx_script)
-        .unwrap();
-
-    let tx_result = client
-        .new_transaction(counter_contract.id(), tx_increment_request)
-        .await
-        .unwrap();
-
-    println!("tx result id: {:?}", tx_result.executed_transaction().id());
-
-    let _ = client.submit_transaction(tx_result).await;
+    println!("\n[STEP 2] Call Counter Contract With Binding");
+
+    // The binding builds the script calling `increment_count`, executes and submits it
+    let counter = accounts::counter::CounterContract::new(counter_contract.id());
+    if dry_run_requested() {
+        let request = accounts::counter::CounterContract::increment_count_call()?.request()?;
+        let report = dry_run(&mut client, &authenticator, counter_contract.id(), request).await?;
+        println!("{report}");
+        return Ok(());
+    }
+    let submitted = counter.increment_count(&mut client).await?;
 
-    tokio::time::sleep(Duration::from_secs(3)).await;
-    client.sync_state().await.unwrap();
+    println!("tx id: {:?}", submitted.tx_id());
 
-    let (account, _data) = client.get_account(counter_contract.id()).await.unwrap();
+    let committed = wait_for_commit(&mut client, submitted, DEFAULT_COMMIT_TIMEOUT).await?;
+    println!("committed in block {}", committed.block_num);
 
-    println!("storage item 0: {:?}", account.storage().get_item(0));
+    let storage = accounts::counter::Storage::new(&committed.account)?;
+    println!("count: {}", storage.count()?);
 
     Ok(())
 }
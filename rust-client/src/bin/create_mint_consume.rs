@@ -3,20 +3,22 @@ use miden_client::{
     assets::{FungibleAsset, TokenSymbol},
     crypto::Digest,
     notes::NoteType,
-    transactions::{OutputNote, PaymentTransactionData, TransactionRequest},
-    ClientError, Felt,
+    transactions::TransactionRequest,
 };
-use miden_lib::notes::create_p2id_note;
 use miden_objects::accounts::get_account_seed;
 use rand::Rng;
 
-use rust_client::common::initialize_client;
+use rust_client::common::{
+    disperse_request, dry_run, dry_run_requested, initialize_client_with_authenticator,
+    parse_payments, send_payment, submit_with_retry, wait_for_consumable_notes, Backoff, BatchMint,
+    DisperseOptions, IdempotentPayment, NoteFilter, PaymentSecret, RetryPolicy,
+};
 
 use tokio::time::Duration;
 
 #[tokio::main]
-async fn main() -> Result<(), ClientError> {
-    let mut client = initialize_client().await?;
+async fn main() -> Result<(), String> {
+    let (mut client, authenticator) = initialize_client_with_authenticator().await?;
 
     //------------------------------------------------------------
     // STEP 1: Create a basic wallet account for Alice
@@ -62,26 +64,40 @@ async fn main() -> Result<(), ClientError> {
     client.sync_state().await?;
     tokio::time::sleep(Duration::from_secs(5)).await;
 
-    for i in 1..=5 {
-        let amount: i32 = 100;
-        let fungible_asset = FungibleAsset::new(faucet_account.id(), 100).unwrap();
-
-        let transaction_request = TransactionRequest::mint_fungible_asset(
-            fungible_asset.clone(),
-            alice_account.id(),
-            NoteType::Public,
-            client.rng(),
+    // All 5 notes are created by a single faucet transaction
+    let transaction_request = BatchMint::new(faucet_account.id(), NoteType::Public)
+        .notes([(alice_account.id(), 100); 5])
+        .request(client.rng())?;
+    let minted_note_ids: Vec<_> = transaction_request
+        .expected_output_notes()
+        .map(|note| note.id())
+        .collect();
+
+    // The later steps need the minted notes, so a dry run stops after the mint
+    if dry_run_requested() {
+        let report = dry_run(
+            &mut client,
+            &authenticator,
+            faucet_account.id(),
+            transaction_request,
         )
-        .unwrap();
-
-        let tx_execution_result = client
-            .new_transaction(faucet_account.id(), transaction_request)
-            .await?;
-
-        client.submit_transaction(tx_execution_result).await?;
-        println!("Minted note #{} of {} tokens for Alice.", i, amount);
+        .await?;
+        println!("{report}");
+        return Ok(());
     }
 
+    submit_with_retry(
+        &mut client,
+        faucet_account.id(),
+        transaction_request,
+        RetryPolicy::default(),
+    )
+    .await?;
+    println!(
+        "Minted {} notes of 100 tokens for Alice.",
+        minted_note_ids.len()
+    );
+
     // Sync state to ensure all notes are visible to the client
     client.sync_state().await?;
     println!("All 5 notes minted for Alice successfully!");
@@ -91,45 +107,38 @@ async fn main() -> Result<(), ClientError> {
     //------------------------------------------------------------
     println!("\n[STEP 4] Alice will now consume all of her notes to consolidate them.");
 
-    // Wait until there are exactly 5 consumable notes for Alice
-    loop {
-        // Re-sync state to ensure we have the latest info
-        client.sync_state().await?;
-
-        // Fetch all consumable notes for Alice
-        let consumable_notes = client
-            .get_consumable_notes(Some(alice_account.id()))
-            .await?;
-        let list_of_note_ids: Vec<_> = consumable_notes.iter().map(|(note, _)| note.id()).collect();
-
-        if list_of_note_ids.len() == 5 {
-            println!(
-                "Alice has {} consumable notes. Consuming them now...",
-                list_of_note_ids.len()
-            );
-
-            let transaction_request = TransactionRequest::consume_notes(list_of_note_ids);
-            let tx_execution_result = client
-                .new_transaction(alice_account.id(), transaction_request)
-                .await?;
-
-            client.submit_transaction(tx_execution_result).await?;
-            println!("Successfully consumed all of Alice's notes.");
-            break;
-        } else {
-            println!(
-                "Currently, Alice has {} consumable notes. Waiting for 5...",
-                list_of_note_ids.len()
-            );
-            tokio::time::sleep(Duration::from_secs(5)).await;
-        }
-    }
+    // Wait until the minted notes are consumable by Alice
+    let notes = wait_for_consumable_notes(
+        &mut client,
+        alice_account.id(),
+        &NoteFilter::Ids(minted_note_ids),
+        Duration::from_secs(60),
+        Backoff::default(),
+    )
+    .await?;
+    println!(
+        "Alice has {} consumable notes. Consuming them now...",
+        notes.len()
+    );
+
+    let list_of_note_ids: Vec<_> = notes.iter().map(|note| note.id()).collect();
+    let transaction_request = TransactionRequest::consume_notes(list_of_note_ids);
+    submit_with_retry(
+        &mut client,
+        alice_account.id(),
+        transaction_request,
+        RetryPolicy::default(),
+    )
+    .await?;
+    println!("Successfully consumed all of Alice's notes.");
+
     //------------------------------------------------------------
     // STEP 5: Using Alice's wallet, send 5 notes of 50 tokens each to list of users
     //------------------------------------------------------------
     println!("\n[STEP 5] Alice sends 5 notes of 50 tokens each to 5 different users.");
 
-    let mut p2id_notes = vec![];
+    // One `recipient,faucet,amount` row per user, as read by the disperse tool
+    let mut payments_csv = String::new();
     for i in 1..=4 {
         // Generate a unique random seed based on the loop index `i`
         let init_seed = {
@@ -155,32 +164,29 @@ async fn main() -> Result<(), ClientError> {
 
         // Specify send amount
         let send_amount = 50;
-        let fungible_asset = FungibleAsset::new(faucet_account.id(), send_amount)
-            .expect("Failed to create fungible asset for sending.");
-
-        let p2id_note = create_p2id_note(
-            alice_account.id(),
-            target_account_id,
-            vec![fungible_asset.into()],
-            NoteType::Public,
-            Felt::new(0),
-            client.rng(),
-        )
-        .unwrap();
-
-        p2id_notes.push(p2id_note);
+        payments_csv.push_str(&format!(
+            "{},{},{send_amount}\n",
+            target_account_id.to_hex(),
+            faucet_account.id().to_hex()
+        ));
     }
-    let output_notes: Vec<OutputNote> = p2id_notes.into_iter().map(OutputNote::Full).collect();
+    let payments = parse_payments(&payments_csv)?;
 
-    let transaction_request = TransactionRequest::new()
-        .with_own_output_notes(output_notes)
-        .unwrap();
-
-    let tx_execution_result = client
-        .new_transaction(alice_account.id(), transaction_request)
-        .await?;
+    // One P2ID note per payment, all created by a single transaction
+    let transaction_request = disperse_request(
+        alice_account.id(),
+        &payments,
+        &DisperseOptions::default(),
+        client.rng(),
+    )?;
 
-    client.submit_transaction(tx_execution_result).await?;
+    submit_with_retry(
+        &mut client,
+        alice_account.id(),
+        transaction_request,
+        RetryPolicy::default(),
+    )
+    .await?;
 
     // Example of sending a single P2ID transaction
 
@@ -188,7 +194,7 @@ async fn main() -> Result<(), ClientError> {
     let init_seed = {
         let mut seed = [0u8; 32];
         rand::thread_rng().fill(&mut seed);
-        seed[0] = 0 as u8;
+        seed[0] = 0;
         seed
     };
 
@@ -199,26 +205,35 @@ async fn main() -> Result<(), ClientError> {
     let send_amount = 50;
     let fungible_asset = FungibleAsset::new(faucet_account.id(), send_amount).unwrap();
 
-    let payment_transaction = PaymentTransactionData::new(
-        vec![fungible_asset.into()],
+    // Serial numbers of payment notes are derived from the sender's payment secret and an
+    // idempotency key, so the same payment always yields the same note. Real senders keep the
+    // secret, e.g. derive it with `MasterSeed::payment_secret`.
+    let payment_secret = PaymentSecret::new(rand::thread_rng().gen());
+    let payment = IdempotentPayment::new(
+        "create-mint-consume/single-payment",
         alice_account.id(),
         target_account_id,
+        vec![fungible_asset.into()],
     );
 
-    // Create a pay-to-id transaction
-    let transaction_request = TransactionRequest::pay_to_id(
-        payment_transaction,
-        None,             // recall_height: None
-        NoteType::Public, // note type is public
-        client.rng(),     // rng
+    let outcome = send_payment(
+        &mut client,
+        &payment,
+        &payment_secret,
+        RetryPolicy::default(),
     )
-    .unwrap();
-
-    let tx_execution_result = client
-        .new_transaction(alice_account.id(), transaction_request)
-        .await?;
-
-    client.submit_transaction(tx_execution_result).await?;
+    .await?;
+    println!("single payment: {:?}", outcome);
+
+    // Retrying the same payment finds its note instead of paying twice
+    let retry = send_payment(
+        &mut client,
+        &payment,
+        &payment_secret,
+        RetryPolicy::default(),
+    )
+    .await?;
+    println!("retried payment: {:?}", retry);
 
     println!("\nAll steps completed successfully!");
     println!("Alice created a wallet, a faucet was deployed,");
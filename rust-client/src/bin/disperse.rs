@@ -0,0 +1,129 @@
+use miden_client::accounts::AccountId;
+use miden_objects::MAX_OUTPUT_NOTES_PER_TX;
+
+use rust_client::common::{
+    disperse, disperse_request, dry_run, dry_run_requested, initialize_client_with_authenticator,
+    read_payments, unpaid_payments, DisperseOptions,
+};
+
+use std::{env, path::PathBuf};
+
+const USAGE: &str = "usage: disperse <sender account ID> <payments.csv> [--journal <path>] \
+                     [--max-notes <count>] [--profile <name>] [--dry-run]";
+
+/// Command line arguments of the disperse tool.
+struct Args {
+    sender_id: AccountId,
+    csv_path: PathBuf,
+    journal_path: PathBuf,
+    max_notes_per_tx: Option<usize>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut positional = Vec::new();
+    let mut journal_path = None;
+    let mut max_notes_per_tx = None;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--journal" => journal_path = Some(PathBuf::from(args.next().ok_or(USAGE)?)),
+            "--max-notes" => {
+                let count = args.next().ok_or(USAGE)?;
+                let max_notes = count
+                    .parse()
+                    .ok()
+                    .filter(|max_notes| (1..=MAX_OUTPUT_NOTES_PER_TX).contains(max_notes))
+                    .ok_or_else(|| {
+                        format!(
+                            "invalid --max-notes `{count}`, expected a number from 1 to \
+                             {MAX_OUTPUT_NOTES_PER_TX}"
+                        )
+                    })?;
+                max_notes_per_tx = Some(max_notes);
+            }
+            // Read by `initialize_client_with_authenticator` and `dry_run_requested`
+            "--profile" => {
+                args.next();
+            }
+            "--dry-run" => {}
+            _ if arg.starts_with("--profile=") => {}
+            _ if arg.starts_with("--") => return Err(format!("unknown flag `{arg}`\n{USAGE}")),
+            _ => positional.push(arg),
+        }
+    }
+
+    let [sender_id, csv_path] = <[String; 2]>::try_from(positional).map_err(|_| USAGE)?;
+    let sender_id = AccountId::from_hex(&sender_id)
+        .map_err(|err| format!("invalid sender account ID `{sender_id}`: {err}"))?;
+    let csv_path = PathBuf::from(csv_path);
+    // By default the journal sits next to the CSV file, e.g. `airdrop.csv.journal`
+    let journal_path = journal_path.unwrap_or_else(|| {
+        let mut path = csv_path.clone().into_os_string();
+        path.push(".journal");
+        PathBuf::from(path)
+    });
+
+    Ok(Args {
+        sender_id,
+        csv_path,
+        journal_path,
+        max_notes_per_tx,
+    })
+}
+
+#[tokio::main]
+async fn main() -> Result<(), String> {
+    let args = parse_args()?;
+    let mut options = DisperseOptions::default();
+    if let Some(max_notes_per_tx) = args.max_notes_per_tx {
+        options.max_notes_per_tx = max_notes_per_tx;
+    }
+
+    let payments = read_payments(&args.csv_path)?;
+    println!(
+        "Read {} payments from {}, journal: {}",
+        payments.len(),
+        args.csv_path.display(),
+        args.journal_path.display()
+    );
+
+    let (mut client, authenticator) = initialize_client_with_authenticator().await?;
+    client.sync_state().await?;
+
+    // A dry run executes the first transaction of the remaining payments
+    if dry_run_requested() {
+        let unpaid = unpaid_payments(args.sender_id, &payments, &args.journal_path)?;
+        if unpaid.is_empty() {
+            println!("All payments were already sent.");
+            return Ok(());
+        }
+        let chunk = &unpaid[..unpaid.len().min(options.chunk_size())];
+        let request = disperse_request(args.sender_id, chunk, &options, client.rng())?;
+        let report = dry_run(&mut client, &authenticator, args.sender_id, request).await?;
+        println!("{report}");
+        return Ok(());
+    }
+
+    let summary = disperse(
+        &mut client,
+        args.sender_id,
+        &payments,
+        &args.journal_path,
+        &options,
+    )
+    .await?;
+
+    println!(
+        "Paid {} payments in {} transactions, {} were paid by earlier runs.",
+        summary.newly_paid, summary.transactions, summary.previously_paid
+    );
+    if summary.unpaid > 0 {
+        println!(
+            "{} payments expired before being committed, run again to send them.",
+            summary.unpaid
+        );
+    }
+
+    Ok(())
+}
@@ -1,52 +1,56 @@
-use miden_client::{accounts::AccountId, transactions::TransactionRequest, ClientError};
+use miden_client::{accounts::AccountId, transactions::TransactionRequest};
 
-use rust_client::common::{create_new_account, initialize_client};
-use std::fs;
-use std::path::Path;
-
-use tokio::time::Duration;
+use rust_client::common::{
+    accounts, dry_run, dry_run_requested, initialize_client_with_authenticator, scripts,
+    submit_with_retry, wait_for_commit, RetryPolicy, DEFAULT_COMMIT_TIMEOUT,
+};
 
 #[tokio::main]
-async fn main() -> Result<(), ClientError> {
-    let mut client = initialize_client().await?;
+async fn main() -> Result<(), String> {
+    let (mut client, authenticator) = initialize_client_with_authenticator().await?;
 
     let counter_contract_id_str = "0x18130bca4c6dc308";
-    let counter_contract_id = AccountId::from_hex(&counter_contract_id_str).unwrap();
-
-    // --- 1) Grab the procedure #2 hash and prepare it for insertion into the script
-    let procedure_hash = "0x2259e69ba0e49a85f80d5ffc348e25a0386a0bbe7dbb58bc45b3f1493a03c725";
-
-    // --- 2) Load MASM script
-    let file_path = Path::new("../masm/scripts/counter_script.masm");
-    let original_code = fs::read_to_string(file_path).expect("Failed to read the file");
+    let counter_contract_id = AccountId::from_hex(counter_contract_id_str).unwrap();
 
-    // --- 3) Replace {increment_count} in the script with the actual call line
-    let replaced_code = original_code.replace("{increment_count}", &procedure_hash);
-    println!("Final script:\n{}", replaced_code);
+    // --- 1) Load the script, precompiled at build time against the contract libraries
+    let script = scripts::COUNTER_SCRIPT;
+    println!("Final script:\n{}", script.source);
 
-    // --- 4) Compile the script (now containing the procedure #2 hash)
-    let tx_script = client.compile_tx_script(vec![], &replaced_code).unwrap();
+    // --- 2) Build the transaction script
+    let tx_script = script.tx_script(vec![]);
 
-    // --- 5) Execute the transaction
+    // --- 3) Execute the transaction
     let tx_increment_request = TransactionRequest::new()
         .with_custom_script(tx_script)
         .unwrap();
 
-    let tx_result = client
-        .new_transaction(counter_contract_id, tx_increment_request)
-        .await
-        .unwrap();
-
-    println!("tx result id: {:?}", tx_result.executed_transaction().id());
-
-    let _ = client.submit_transaction(tx_result).await;
-
-    tokio::time::sleep(Duration::from_secs(3)).await;
-    client.sync_state().await.unwrap();
-
-    let (account, _data) = client.get_account(counter_contract_id).await.unwrap();
-
-    println!("storage item 0: {:?}", account.storage().get_item(0));
+    if dry_run_requested() {
+        let report = dry_run(
+            &mut client,
+            &authenticator,
+            counter_contract_id,
+            tx_increment_request,
+        )
+        .await?;
+        println!("{report}");
+        return Ok(());
+    }
+
+    let submitted = submit_with_retry(
+        &mut client,
+        counter_contract_id,
+        tx_increment_request,
+        RetryPolicy::default(),
+    )
+    .await?;
+    println!("tx result id: {:?}", submitted.tx_id());
+
+    let committed = wait_for_commit(&mut client, submitted, DEFAULT_COMMIT_TIMEOUT).await?;
+    println!("committed in block {}", committed.block_num);
+    let account = committed.account;
+
+    let counter = accounts::counter::Storage::new(&account)?;
+    println!("count: {}", counter.count()?);
 
     Ok(())
 }
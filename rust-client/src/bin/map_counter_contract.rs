@@ -0,0 +1,190 @@
+use miden_client::{
+    accounts::{AccountData, AccountStorageMode, AccountTemplate},
+    crypto::FeltRng,
+    notes::{
+        Note, NoteAssets, NoteExecutionHint, NoteExecutionMode, NoteInputs, NoteMetadata,
+        NoteRecipient, NoteTag, NoteType,
+    },
+    transactions::{OutputNote, TransactionRequest},
+    Felt,
+};
+
+use rust_client::common::{
+    accounts, dry_run, dry_run_requested, initialize_client_with_authenticator, notes,
+    read_map_entry, scripts, submit_with_retry, wait_for_commit, KeyGeneration, RetryPolicy,
+    DEFAULT_COMMIT_TIMEOUT,
+};
+
+#[tokio::main]
+async fn main() -> Result<(), String> {
+    let (mut client, authenticator) = initialize_client_with_authenticator().await?;
+
+    //------------------------------------------------------------
+    // STEP 1: Create a per-account counter contract
+    //------------------------------------------------------------
+    println!("\n[STEP 1] Creating Map Counter Contract.");
+
+    // Counts are kept in a storage map, keyed by the account id of the caller
+    let contract = accounts::MAP_COUNTER;
+    let (map_counter_contract, map_counter_seed, auth_secret_key) =
+        contract.create_account(KeyGeneration::Random)?;
+
+    println!(
+        "map_counter_contract accountId: {:?}",
+        map_counter_contract.id().to_hex()
+    );
+
+    let map_counter_contract_account_data = AccountData::new(
+        map_counter_contract.clone(),
+        map_counter_seed,
+        auth_secret_key.clone(),
+    );
+
+    // Import to client
+    client
+        .import_account(map_counter_contract_account_data)
+        .await
+        .unwrap();
+
+    //------------------------------------------------------------
+    // STEP 2: Increment the count of the contract account itself
+    //------------------------------------------------------------
+    println!("\n[STEP 2] Call Map Counter Contract With Script");
+
+    // The script increments the entry keyed by the executing account. Notes built from
+    // `notes::MAP_COUNTER_NOTE` increment the entry of their sender instead, see step 4.
+    let script = scripts::MAP_COUNTER_SCRIPT;
+    println!("Final script:\n{}", script.source);
+
+    let tx_increment_request = TransactionRequest::new()
+        .with_custom_script(script.tx_script(vec![]))
+        .unwrap();
+
+    if dry_run_requested() {
+        let report = dry_run(
+            &mut client,
+            &authenticator,
+            map_counter_contract.id(),
+            tx_increment_request,
+        )
+        .await?;
+        println!("{report}");
+        return Ok(());
+    }
+
+    let submitted = submit_with_retry(
+        &mut client,
+        map_counter_contract.id(),
+        tx_increment_request,
+        RetryPolicy::default(),
+    )
+    .await?;
+    println!("tx result id: {:?}", submitted.tx_id());
+
+    let committed = wait_for_commit(&mut client, submitted, DEFAULT_COMMIT_TIMEOUT).await?;
+    println!("committed in block {}", committed.block_num);
+
+    //------------------------------------------------------------
+    // STEP 3: Read the count of one account from the map
+    //------------------------------------------------------------
+    println!("\n[STEP 3] Read Map Entry");
+
+    let schema = contract.manifest()?.schema()?;
+    let count: u64 = read_map_entry(
+        &mut client,
+        map_counter_contract.id(),
+        &schema,
+        "counts",
+        map_counter_contract.id(),
+    )
+    .await?;
+    println!("count of {}: {}", map_counter_contract.id().to_hex(), count);
+
+    // The generated accessors read the same entry from an already loaded account
+    let account = committed.account;
+    let map_counter = accounts::map_counter::Storage::new(&account)?;
+    println!("counts: {}", map_counter.counts(account.id())?);
+
+    //------------------------------------------------------------
+    // STEP 4: Send a counter note to the contract from a wallet
+    //------------------------------------------------------------
+    println!("\n[STEP 4] Send Map Counter Note From A Wallet");
+
+    let wallet_template = AccountTemplate::BasicWallet {
+        mutable_code: true,
+        storage_mode: AccountStorageMode::Public,
+    };
+    let (wallet, _wallet_seed) = client.new_account(wallet_template).await?;
+    println!("wallet accountId: {:?}", wallet.id().to_hex());
+
+    // The note increments the entry of its sender, the wallet, when the contract consumes it
+    let recipient = NoteRecipient::new(
+        client.rng().draw_word(),
+        notes::MAP_COUNTER_NOTE.note_script(),
+        NoteInputs::new(vec![]).unwrap(),
+    );
+    let metadata = NoteMetadata::new(
+        wallet.id(),
+        NoteType::Public,
+        NoteTag::from_account_id(map_counter_contract.id(), NoteExecutionMode::Local).unwrap(),
+        NoteExecutionHint::always(),
+        Felt::new(0),
+    )
+    .unwrap();
+    let counter_note = Note::new(NoteAssets::default(), metadata, recipient);
+
+    let note_request = TransactionRequest::new()
+        .with_own_output_notes(vec![OutputNote::Full(counter_note.clone())])
+        .unwrap();
+    let submitted = submit_with_retry(
+        &mut client,
+        wallet.id(),
+        note_request,
+        RetryPolicy::default(),
+    )
+    .await?;
+    let committed = wait_for_commit(&mut client, submitted, DEFAULT_COMMIT_TIMEOUT).await?;
+    println!(
+        "note {} committed in block {}",
+        counter_note.id(),
+        committed.block_num
+    );
+
+    //------------------------------------------------------------
+    // STEP 5: Consume the note with the contract
+    //------------------------------------------------------------
+    println!("\n[STEP 5] Consume Map Counter Note");
+
+    let consume_request =
+        TransactionRequest::new().with_unauthenticated_input_notes([(counter_note, None)]);
+    let submitted = submit_with_retry(
+        &mut client,
+        map_counter_contract.id(),
+        consume_request,
+        RetryPolicy::default(),
+    )
+    .await?;
+    wait_for_commit(&mut client, submitted, DEFAULT_COMMIT_TIMEOUT).await?;
+
+    //------------------------------------------------------------
+    // STEP 6: Read the count of the wallet, kept apart from the contract's own count
+    //------------------------------------------------------------
+    println!("\n[STEP 6] Read Map Entries Of Both Callers");
+
+    for (caller, account_id) in [
+        ("wallet", wallet.id()),
+        ("contract", map_counter_contract.id()),
+    ] {
+        let count: u64 = read_map_entry(
+            &mut client,
+            map_counter_contract.id(),
+            &schema,
+            "counts",
+            account_id,
+        )
+        .await?;
+        println!("count of {caller} {}: {}", account_id.to_hex(), count);
+    }
+
+    Ok(())
+}
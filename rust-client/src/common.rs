@@ -1,93 +1,205 @@
 use miden_client::{
-    accounts::{Account, AccountStorageMode},
-    config::RpcConfig,
-    crypto::RpoRandomCoin,
-    rpc::TonicRpcClient,
-    store::{
-        sqlite_store::{config::SqliteStoreConfig, SqliteStore},
-        StoreAuthenticator,
-    },
-    transactions::{LocalTransactionProver, ProvingOptions},
-    Client, ClientError, Felt,
+    accounts::Account, auth::TransactionAuthenticator, crypto::RpoRandomCoin, Client, ClientError,
 };
-use miden_lib::accounts::auth::RpoFalcon512;
 use miden_objects::{
-    accounts::{AccountBuilder, AccountComponent, AuthSecretKey},
-    crypto::dsa::rpo_falcon512::{PublicKey, SecretKey},
+    accounts::{AccountComponent, AuthSecretKey},
+    crypto::dsa::rpo_falcon512::SecretKey,
     Word,
 };
 
-use figment::{
-    providers::{Format, Toml},
-    Figment,
-};
 use rand::Rng;
 use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
 
-use serde::Deserialize;
-use std::{path::Path, sync::Arc};
+use std::{fmt, sync::Arc};
+
+mod account;
+pub use account::{create_account, AccountAuth, AccountOptions};
+
+mod advice;
+pub use advice::{AdviceBuilder, AdviceData, AdviceEntry, AdviceError, AdviceValue};
+
+mod builder;
+pub use builder::ClientBuilder;
+
+mod cache;
+pub use cache::{MastCache, MAST_CACHE_DIR_ENV_VAR};
+
+mod call;
+pub use call::{CallError, ProcedureCall, StackArg};
+
+mod config;
+pub use config::{
+    config_search_paths, selected_profile, ClientConfig, ConfigError, KeyStoreConfig,
+    CLIENT_CONFIG_FILE_NAME, KEYSTORE_PASSWORD_ENV_VAR,
+};
+
+mod disperse;
+pub use disperse::{
+    disperse, disperse_request, parse_payments, read_payments, unpaid_payments, DisperseError,
+    DisperseOptions, DisperseSummary, Payment,
+};
+
+mod dry_run;
+pub use dry_run::{
+    dry_run, dry_run_requested, DryRunError, DryRunReport, MapEntryDiff, NoteSummary, SlotDiff,
+    DRY_RUN_ENV_VAR,
+};
+
+mod keystore;
+pub use keystore::{FilesystemKeyStore, KeyStoreAuthenticator, KeyStoreError};
+
+mod library;
+pub use library::{
+    compile_note_script_with_libraries, compile_tx_script_with_libraries, create_library,
+    LibraryError, EXTERNAL_CONTRACT_NAMESPACE,
+};
+
+mod manifest;
+pub use manifest::{
+    AccountManifest, ManifestError, ManifestWord, MapEntryManifest, ParamManifest,
+    ProcedureContext, ProcedureManifest, SlotContent, SlotManifest,
+};
+
+mod mint;
+pub use mint::{BatchMint, MintError, MintNote};
+
+mod payment;
+pub use payment::{
+    payment_status, send_payment, IdempotentPayment, PaymentError, PaymentOutcome, PaymentSecret,
+    PaymentStatus, PAYMENT_EXPIRATION_DELTA,
+};
+
+mod procedures;
+pub use procedures::{ProcedureError, ProcedureRoots};
+
+mod registry;
+pub use registry::{
+    accounts, notes, scripts, ContractArtifact, NoteScriptArtifact, TxScriptArtifact,
+};
+
+mod seed;
+pub use seed::{MasterSeed, MasterSeedError};
+
+mod storage;
+pub use storage::{
+    component_storage_offset, read_map_entry, SlotKind, SlotSchema, SlotType, StorageError,
+    StorageSchema, StorageValue, StorageView,
+};
+
+mod submit;
+pub use submit::{
+    submit_executed_with_retry, submit_with_retry, RetryPolicy, SubmitError, SubmitErrorKind,
+    SubmitOutcome,
+};
+
+mod template;
+pub use template::{MasmTemplate, TemplateError, TemplateValue};
 
-/// Name of your local TOML file containing client config.
-/// Adjust this if you store it in another place/name.
-const CLIENT_CONFIG_FILE_NAME: &str = "miden-client.toml";
+mod wait;
+pub use wait::{
+    wait_for_commit, wait_for_consumable_notes, Backoff, CommittedTransaction, NoteFilter,
+    WaitError, DEFAULT_COMMIT_TIMEOUT,
+};
 
-/// Simple container for everything in your TOML file (RPC + store configs, etc.)
-#[derive(Debug, Deserialize)]
-pub struct ClientConfig {
-    /// Describes settings related to the RPC endpoint
-    pub rpc: RpcConfig,
-    /// Describes settings related to the store.
-    pub store: SqliteStoreConfig,
+/// Errors that can occur while setting up a `Client` with [`ClientBuilder`].
+#[derive(Debug)]
+pub enum ClientSetupError {
+    /// The client config could not be loaded or is invalid.
+    Config(ConfigError),
+    /// The keystore could not be opened.
+    KeyStore(KeyStoreError),
+    /// The store or another client component could not be created.
+    Client(ClientError),
 }
 
-impl ClientConfig {
-    pub fn from_file<P: AsRef<Path>>(path: P) -> Self {
-        let figment = Figment::from(Toml::file(path));
-        figment.extract().unwrap_or_else(|e| {
-            panic!("Failed to load client config: {}", e);
-        })
+impl fmt::Display for ClientSetupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientSetupError::Config(err) => write!(f, "invalid client config: {err}"),
+            ClientSetupError::KeyStore(err) => write!(f, "failed to open keystore: {err}"),
+            ClientSetupError::Client(err) => write!(f, "failed to create client: {err}"),
+        }
     }
 }
 
-/// This function initializes the `Client` using the parameters
-/// from `miden-client.toml`. It loads the store, seeds the RNG,
-/// sets up the authenticator, local prover, and returns a `Client`.
-pub async fn initialize_client() -> Result<Client<RpoRandomCoin>, ClientError> {
-    let client_config = ClientConfig::from_file(CLIENT_CONFIG_FILE_NAME);
+impl std::error::Error for ClientSetupError {}
 
-    let store = SqliteStore::new(&client_config.store)
-        .await
-        .map_err(ClientError::StoreError)?;
-    let arc_store = Arc::new(store);
+impl From<ConfigError> for ClientSetupError {
+    fn from(err: ConfigError) -> Self {
+        Self::Config(err)
+    }
+}
 
-    let mut seed_rng = rand::thread_rng();
-    let coin_seed: [u64; 4] = seed_rng.gen();
-    let rng_for_auth = RpoRandomCoin::new(coin_seed.map(Felt::new));
-    let rng_for_client = RpoRandomCoin::new(coin_seed.map(Felt::new));
+impl From<KeyStoreError> for ClientSetupError {
+    fn from(err: KeyStoreError) -> Self {
+        Self::KeyStore(err)
+    }
+}
 
-    let authenticator = StoreAuthenticator::new_with_rng(arc_store.clone(), rng_for_auth);
+impl From<ClientError> for ClientSetupError {
+    fn from(err: ClientError) -> Self {
+        Self::Client(err)
+    }
+}
 
-    let tx_prover = LocalTransactionProver::new(ProvingOptions::default());
+impl From<ClientSetupError> for String {
+    fn from(err: ClientSetupError) -> String {
+        err.to_string()
+    }
+}
 
-    let rpc_client = Box::new(TonicRpcClient::new(&client_config.rpc));
+/// This function initializes the `Client` using the parameters
+/// from the selected profile of `miden-client.toml`. It loads the store, seeds the RNG,
+/// sets up the authenticator, local prover, and returns a `Client`.
+///
+/// The config file is looked up in the locations returned by [`config_search_paths`]. If the
+/// config has a `keystore` section, signatures are produced from the encrypted key files in
+/// that directory, falling back to the keys kept in the store. Use [`ClientBuilder`] to
+/// replace any of these parts.
+pub async fn initialize_client() -> Result<Client<RpoRandomCoin>, ClientSetupError> {
+    ClientBuilder::new().build().await
+}
 
-    let client = Client::new(
-        rpc_client,
-        rng_for_client,
-        arc_store,
-        Arc::new(authenticator),
-        Arc::new(tx_prover),
-        true,
-    );
+/// Initializes the `Client` like [`initialize_client`], and also returns its authenticator for
+/// [`dry_run`].
+pub async fn initialize_client_with_authenticator(
+) -> Result<(Client<RpoRandomCoin>, Arc<dyn TransactionAuthenticator>), ClientSetupError> {
+    ClientBuilder::new().build_with_authenticator().await
+}
 
-    Ok(client)
+/// Source of randomness used to generate account keys and seeds.
+#[derive(Clone, Copy, Debug, Default)]
+pub enum KeyGeneration {
+    /// Keys are generated from OS entropy, so every account gets its own key pair.
+    #[default]
+    Random,
+    /// Keys are derived from the given seed, so the same seed always yields the same key pair.
+    /// The seed must stay secret: use fixed seeds only in tests, and [`MasterSeed`] to derive
+    /// secret seeds from a backup phrase.
+    Deterministic([u8; 32]),
 }
 
+impl KeyGeneration {
+    fn rng(self) -> ChaCha20Rng {
+        match self {
+            KeyGeneration::Random => ChaCha20Rng::from_entropy(),
+            KeyGeneration::Deterministic(seed) => ChaCha20Rng::from_seed(seed),
+        }
+    }
+}
+
+/// Generates a new Falcon key pair from OS entropy.
 pub fn get_new_pk_and_authenticator() -> (Word, AuthSecretKey) {
-    let seed = [0_u8; 32];
-    let mut rng = ChaCha20Rng::from_seed(seed);
+    get_pk_and_authenticator(KeyGeneration::Random)
+}
 
-    let sec_key = SecretKey::with_rng(&mut rng);
+/// Generates a Falcon key pair using the given [`KeyGeneration`] mode.
+pub fn get_pk_and_authenticator(key_generation: KeyGeneration) -> (Word, AuthSecretKey) {
+    generate_pk_and_authenticator(&mut key_generation.rng())
+}
+
+fn generate_pk_and_authenticator<R: Rng>(rng: &mut R) -> (Word, AuthSecretKey) {
+    let sec_key = SecretKey::with_rng(rng);
     let pub_key: Word = sec_key.public_key().into();
 
     let auth_secret_key = AuthSecretKey::RpoFalcon512(sec_key);
@@ -95,18 +207,26 @@ pub fn get_new_pk_and_authenticator() -> (Word, AuthSecretKey) {
     (pub_key, auth_secret_key)
 }
 
+/// Creates a new public account with `account_component` and a fresh random Falcon key.
+///
+/// Use [`create_account`] for private accounts, faucets, several components or other
+/// authentication components.
 pub fn create_new_account(
     account_component: AccountComponent,
 ) -> (Account, Option<Word>, AuthSecretKey) {
-    let (pub_key, auth_secret_key) = get_new_pk_and_authenticator();
-
-    let (account, seed) = AccountBuilder::new()
-        .init_seed(ChaCha20Rng::from_entropy().gen())
-        .storage_mode(AccountStorageMode::Public)
-        .with_component(account_component)
-        .with_component(RpoFalcon512::new(PublicKey::new(pub_key)))
-        .build()
-        .unwrap();
+    create_new_account_with_keys(account_component, KeyGeneration::Random)
+}
 
-    (account, Some(seed), auth_secret_key)
+/// Creates a new public account with `account_component`, generating both the Falcon key and
+/// the account seed using the given [`KeyGeneration`] mode.
+pub fn create_new_account_with_keys(
+    account_component: AccountComponent,
+    key_generation: KeyGeneration,
+) -> (Account, Option<Word>, AuthSecretKey) {
+    create_account(
+        AccountOptions::new()
+            .with_component(account_component)
+            .key_generation(key_generation),
+    )
+    .unwrap()
 }
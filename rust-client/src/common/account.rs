@@ -0,0 +1,135 @@
+use miden_client::accounts::{Account, AccountStorageMode, AccountType};
+use miden_lib::accounts::auth::RpoFalcon512;
+use miden_objects::{
+    accounts::{AccountBuilder, AccountComponent, AuthSecretKey},
+    crypto::dsa::rpo_falcon512::PublicKey,
+    AccountError, Word,
+};
+
+use rand::Rng;
+
+use super::{generate_pk_and_authenticator, KeyGeneration};
+
+/// Authentication component added to accounts created with [`create_account`].
+#[derive(Clone, Debug, Default)]
+pub enum AccountAuth {
+    /// `RpoFalcon512` with a new key generated using the [`KeyGeneration`] mode of the options.
+    #[default]
+    RpoFalcon512,
+    /// `RpoFalcon512` with an existing key, e.g. one loaded from the keystore.
+    RpoFalcon512WithKey(AuthSecretKey),
+    /// A custom authentication component together with the secret key the client's
+    /// authenticator signs with for it.
+    Custom {
+        component: AccountComponent,
+        secret_key: AuthSecretKey,
+    },
+}
+
+/// Options of the accounts created with [`create_account`].
+///
+/// The defaults match [`create_new_account`](super::create_new_account): a public, regular
+/// account with updatable code, authenticated with a new random `RpoFalcon512` key.
+#[derive(Clone, Debug)]
+pub struct AccountOptions {
+    storage_mode: AccountStorageMode,
+    account_type: AccountType,
+    components: Vec<AccountComponent>,
+    auth: AccountAuth,
+    key_generation: KeyGeneration,
+}
+
+impl Default for AccountOptions {
+    fn default() -> Self {
+        Self {
+            storage_mode: AccountStorageMode::Public,
+            account_type: AccountType::RegularAccountUpdatableCode,
+            components: Vec::new(),
+            auth: AccountAuth::default(),
+            key_generation: KeyGeneration::default(),
+        }
+    }
+}
+
+impl AccountOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether the account state is stored on chain (public) or only locally (private).
+    pub fn storage_mode(mut self, storage_mode: AccountStorageMode) -> Self {
+        self.storage_mode = storage_mode;
+        self
+    }
+
+    /// Sets the account type, e.g. a regular account with immutable code or a faucet. Every
+    /// component must support this type.
+    pub fn account_type(mut self, account_type: AccountType) -> Self {
+        self.account_type = account_type;
+        self
+    }
+
+    /// Adds a component to the account. Components are added in the order of the calls,
+    /// before the authentication component.
+    pub fn with_component(mut self, component: impl Into<AccountComponent>) -> Self {
+        self.components.push(component.into());
+        self
+    }
+
+    /// Sets the authentication component of the account.
+    pub fn auth(mut self, auth: AccountAuth) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    /// Sets how the account seed and, for [`AccountAuth::RpoFalcon512`], the key are generated.
+    pub fn key_generation(mut self, key_generation: KeyGeneration) -> Self {
+        self.key_generation = key_generation;
+        self
+    }
+}
+
+/// Creates a new account described by `options`. Returns the account, its seed and the secret
+/// key of its authentication component.
+///
+/// # Errors
+///
+/// Returns an error if a component does not support the account type or the components'
+/// storage or procedures do not fit into one account.
+pub fn create_account(
+    options: AccountOptions,
+) -> Result<(Account, Option<Word>, AuthSecretKey), AccountError> {
+    let mut rng = options.key_generation.rng();
+
+    let (auth_component, auth_secret_key) = match options.auth {
+        AccountAuth::RpoFalcon512 => {
+            let (pub_key, auth_secret_key) = generate_pk_and_authenticator(&mut rng);
+            (
+                RpoFalcon512::new(PublicKey::new(pub_key)).into(),
+                auth_secret_key,
+            )
+        }
+        AccountAuth::RpoFalcon512WithKey(auth_secret_key) => {
+            let AuthSecretKey::RpoFalcon512(ref secret_key) = auth_secret_key;
+            (
+                RpoFalcon512::new(secret_key.public_key()).into(),
+                auth_secret_key,
+            )
+        }
+        AccountAuth::Custom {
+            component,
+            secret_key,
+        } => (component, secret_key),
+    };
+
+    let builder = options.components.into_iter().fold(
+        AccountBuilder::new()
+            .init_seed(rng.gen())
+            .account_type(options.account_type)
+            .storage_mode(options.storage_mode),
+        |builder, component| builder.with_component(component),
+    );
+    let (account, seed) = builder.with_component(auth_component).build()?;
+
+    Ok((account, Some(seed), auth_secret_key))
+}
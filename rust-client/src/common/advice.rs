@@ -0,0 +1,449 @@
+use miden_client::{transactions::TransactionRequest, Felt, StarkField, Word};
+use miden_objects::{
+    crypto::{
+        hash::rpo::{Rpo256, RpoDigest},
+        merkle::{InnerNodeInfo, MerkleError, MerklePath},
+    },
+    Digest,
+};
+
+use serde::Deserialize;
+use std::{
+    collections::BTreeSet,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Most elements a single `adv_push` instruction moves to the operand stack.
+const MAX_ADV_PUSH: usize = 16;
+
+/// Errors that can occur while building or loading [`AdviceData`].
+#[derive(Debug)]
+pub enum AdviceError {
+    /// The advice file could not be read.
+    Io(PathBuf, io::Error),
+    /// The advice file is not valid JSON or does not match the advice file format.
+    Malformed(String),
+    /// Two entries share the same name.
+    DuplicateEntry(String),
+    /// No entry has this name.
+    UnknownEntry(String),
+    /// A value is not a valid field element.
+    InvalidFelt { entry: String, value: u64 },
+    /// A Merkle path does not open the given leaf index.
+    InvalidMerklePath { entry: String, err: MerkleError },
+}
+
+impl fmt::Display for AdviceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AdviceError::Io(path, err) => write!(f, "failed to read {}: {err}", path.display()),
+            AdviceError::Malformed(err) => write!(f, "failed to parse advice inputs: {err}"),
+            AdviceError::DuplicateEntry(name) => {
+                write!(f, "advice entry `{name}` is declared twice")
+            }
+            AdviceError::UnknownEntry(name) => write!(f, "unknown advice entry `{name}`"),
+            AdviceError::InvalidFelt { entry, value } => {
+                write!(
+                    f,
+                    "value {value} of advice entry `{entry}` is not a field element"
+                )
+            }
+            AdviceError::InvalidMerklePath { entry, err } => {
+                write!(f, "invalid Merkle path in advice entry `{entry}`: {err}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AdviceError {}
+
+impl From<AdviceError> for String {
+    fn from(err: AdviceError) -> String {
+        err.to_string()
+    }
+}
+
+/// A Rust value to provide to a transaction as advice.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AdviceValue {
+    /// A single field element.
+    U64(u64),
+    /// Four field elements.
+    Word([u64; 4]),
+    /// Bytes, packed as their length followed by little-endian `u32` chunks. Hex encoded in
+    /// advice files.
+    #[serde(deserialize_with = "deserialize_hex")]
+    Bytes(Vec<u8>),
+    /// The opening of `leaf` at `index` of a Merkle tree, sibling nodes from the leaf up.
+    MerklePath {
+        leaf: [u64; 4],
+        index: u64,
+        siblings: Vec<[u64; 4]>,
+    },
+}
+
+/// A named advice value with its advice key.
+///
+/// Values are stored in the advice map under the hash of their elements and Merkle paths in
+/// the Merkle store under the root they open to, so the key of a value changes with the value.
+/// Scripts receive the key through a [`MasmTemplate`](super::MasmTemplate) placeholder and read
+/// the value with the instructions of [`AdviceEntry::masm_load`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AdviceEntry {
+    name: String,
+    value: AdviceValue,
+    key: Digest,
+    elements: Vec<Felt>,
+}
+
+impl AdviceEntry {
+    fn new(name: String, value: AdviceValue) -> Result<Self, AdviceError> {
+        let elements = match &value {
+            AdviceValue::U64(value) => vec![felt(&name, *value)?],
+            AdviceValue::Word(word) => word_felts(&name, word)?.to_vec(),
+            AdviceValue::Bytes(bytes) => pack_bytes(bytes),
+            AdviceValue::MerklePath { .. } => Vec::new(),
+        };
+        let key = match &value {
+            AdviceValue::MerklePath {
+                leaf,
+                index,
+                siblings,
+            } => {
+                let (path, leaf) = merkle_path(&name, leaf, siblings)?;
+                path.compute_root(*index, leaf)
+                    .map_err(|err| AdviceError::InvalidMerklePath {
+                        entry: name.clone(),
+                        err,
+                    })?
+            }
+            _ => Rpo256::hash_elements(&elements),
+        };
+
+        Ok(Self {
+            name,
+            value,
+            key,
+            elements,
+        })
+    }
+
+    /// Returns the name of the entry.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the value of the entry.
+    pub fn value(&self) -> &AdviceValue {
+        &self.value
+    }
+
+    /// Returns the advice map key of the value, or the root of the Merkle path.
+    pub fn key(&self) -> Digest {
+        self.key
+    }
+
+    /// Returns the key as a word, to bind to a template placeholder followed by `push.`.
+    pub fn key_word(&self) -> Word {
+        self.key.into()
+    }
+
+    /// Returns the elements stored in the advice map, empty for Merkle paths.
+    pub fn elements(&self) -> &[Felt] {
+        &self.elements
+    }
+
+    /// Returns MASM instructions moving the elements of the entry from the advice map to the
+    /// advice stack, for a procedure reading them with `adv_push` or `adv_loadw`. The first
+    /// element ends up on top of the advice stack.
+    ///
+    /// Merkle paths are read from the Merkle store, see [`AdviceEntry::masm_load`].
+    pub fn masm_stage(&self) -> String {
+        format!(
+            "push.{} adv.push_mapval dropw",
+            format_word(self.key_word())
+        )
+    }
+
+    /// Returns MASM instructions reading the entry onto the operand stack:
+    ///
+    /// - `u64`: `[value]`,
+    /// - `word`: `[w3, w2, w1, w0]`, the word as `push.w0.w1.w2.w3` would push it,
+    /// - `bytes`: `[chunk_n, ..., chunk_1, length]`,
+    /// - `merkle_path`: `[LEAF, ROOT]`, read with `mtree_get`.
+    pub fn masm_load(&self) -> String {
+        match &self.value {
+            AdviceValue::MerklePath {
+                index, siblings, ..
+            } => format!(
+                "push.{} push.{index} push.{} mtree_get",
+                format_word(self.key_word()),
+                siblings.len()
+            ),
+            _ => {
+                let mut masm = self.masm_stage();
+                let mut remaining = self.elements.len();
+                while remaining > 0 {
+                    let count = remaining.min(MAX_ADV_PUSH);
+                    masm.push_str(&format!(" adv_push.{count}"));
+                    remaining -= count;
+                }
+                masm
+            }
+        }
+    }
+
+    fn merkle_nodes(&self) -> Vec<InnerNodeInfo> {
+        match &self.value {
+            AdviceValue::MerklePath {
+                leaf,
+                index,
+                siblings,
+            } => {
+                let (path, leaf) = merkle_path(&self.name, leaf, siblings)
+                    .expect("Merkle path is validated when the entry is built");
+                path.inner_nodes(*index, leaf)
+                    .expect("Merkle path is validated when the entry is built")
+                    .collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Builds typed advice inputs, replacing hand-written advice map entries.
+///
+/// ```ignore
+/// let advice = AdviceBuilder::new().u64("input", 505).build()?;
+/// let source = MasmTemplate::from_file("script.masm")?
+///     .render([("input_key", advice.entry("input")?.key_word())])?;
+/// let request = advice.apply(TransactionRequest::new());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct AdviceBuilder {
+    values: Vec<(String, AdviceValue)>,
+}
+
+impl AdviceBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a single field element.
+    pub fn u64(self, name: &str, value: u64) -> Self {
+        self.value(name, AdviceValue::U64(value))
+    }
+
+    /// Adds a word.
+    pub fn word(self, name: &str, word: Word) -> Self {
+        self.value(name, AdviceValue::Word(word.map(|felt| felt.as_int())))
+    }
+
+    /// Adds a byte blob.
+    pub fn bytes(self, name: &str, bytes: &[u8]) -> Self {
+        self.value(name, AdviceValue::Bytes(bytes.to_vec()))
+    }
+
+    /// Adds the opening of `leaf` at `index` through `path`, whose inner nodes are added to the
+    /// Merkle store.
+    pub fn merkle_path(self, name: &str, leaf: Word, index: u64, path: &MerklePath) -> Self {
+        let value = AdviceValue::MerklePath {
+            leaf: leaf.map(|felt| felt.as_int()),
+            index,
+            siblings: path
+                .nodes()
+                .iter()
+                .map(|node| Word::from(*node).map(|felt| felt.as_int()))
+                .collect(),
+        };
+        self.value(name, value)
+    }
+
+    /// Adds a value.
+    pub fn value(mut self, name: &str, value: AdviceValue) -> Self {
+        self.values.push((name.to_string(), value));
+        self
+    }
+
+    /// Computes the keys of the values.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if two values share a name, a number is not a field element or a
+    /// Merkle path does not have a node at its index.
+    pub fn build(self) -> Result<AdviceData, AdviceError> {
+        let mut names = BTreeSet::new();
+        let mut entries = Vec::with_capacity(self.values.len());
+        for (name, value) in self.values {
+            if !names.insert(name.clone()) {
+                return Err(AdviceError::DuplicateEntry(name));
+            }
+            entries.push(AdviceEntry::new(name, value)?);
+        }
+
+        Ok(AdviceData { entries })
+    }
+}
+
+/// Advice inputs built with [`AdviceBuilder`] or loaded from a JSON file.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AdviceData {
+    entries: Vec<AdviceEntry>,
+}
+
+/// An entry of an advice file.
+#[derive(Deserialize)]
+struct AdviceFileEntry {
+    name: String,
+    #[serde(flatten)]
+    value: AdviceValue,
+}
+
+impl AdviceData {
+    /// Parses advice inputs from JSON, a list of named values:
+    ///
+    /// ```json
+    /// [
+    ///     { "name": "input", "u64": 505 },
+    ///     { "name": "point", "word": [1, 2, 3, 4] },
+    ///     { "name": "message", "bytes": "68656c6c6f" },
+    ///     { "name": "proof", "merkle_path": { "leaf": [1, 0, 0, 0], "index": 2, "siblings": [[2, 0, 0, 0], [3, 0, 0, 0]] } }
+    /// ]
+    /// ```
+    pub fn from_json(json: &str) -> Result<Self, AdviceError> {
+        let entries: Vec<AdviceFileEntry> =
+            serde_json::from_str(json).map_err(|err| AdviceError::Malformed(err.to_string()))?;
+
+        entries
+            .into_iter()
+            .fold(AdviceBuilder::new(), |builder, entry| {
+                builder.value(&entry.name, entry.value)
+            })
+            .build()
+    }
+
+    /// Reads and parses the advice file at `path`, see [`AdviceData::from_json`].
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, AdviceError> {
+        let path = path.as_ref();
+        let json =
+            fs::read_to_string(path).map_err(|err| AdviceError::Io(path.to_path_buf(), err))?;
+        Self::from_json(&json)
+    }
+
+    /// Returns the entries, in the order they were added.
+    pub fn entries(&self) -> &[AdviceEntry] {
+        &self.entries
+    }
+
+    /// Returns the entry called `name`.
+    pub fn entry(&self, name: &str) -> Result<&AdviceEntry, AdviceError> {
+        self.entries
+            .iter()
+            .find(|entry| entry.name == name)
+            .ok_or_else(|| AdviceError::UnknownEntry(name.to_string()))
+    }
+
+    /// Returns the advice map entries of the values.
+    pub fn advice_map(&self) -> Vec<(Digest, Vec<Felt>)> {
+        self.entries
+            .iter()
+            .filter(|entry| !matches!(entry.value, AdviceValue::MerklePath { .. }))
+            .map(|entry| (entry.key, entry.elements.clone()))
+            .collect()
+    }
+
+    /// Returns the Merkle store nodes of the Merkle paths.
+    pub fn merkle_nodes(&self) -> Vec<InnerNodeInfo> {
+        self.entries
+            .iter()
+            .flat_map(AdviceEntry::merkle_nodes)
+            .collect()
+    }
+
+    /// Adds the advice map entries and Merkle store nodes to `request`.
+    pub fn apply(&self, request: TransactionRequest) -> TransactionRequest {
+        request
+            .extend_advice_map(self.advice_map())
+            .extend_merkle_store(self.merkle_nodes())
+    }
+}
+
+fn felt(entry: &str, value: u64) -> Result<Felt, AdviceError> {
+    if value >= Felt::MODULUS {
+        return Err(AdviceError::InvalidFelt {
+            entry: entry.to_string(),
+            value,
+        });
+    }
+    Ok(Felt::new(value))
+}
+
+fn word_felts(entry: &str, word: &[u64; 4]) -> Result<Word, AdviceError> {
+    Ok([
+        felt(entry, word[0])?,
+        felt(entry, word[1])?,
+        felt(entry, word[2])?,
+        felt(entry, word[3])?,
+    ])
+}
+
+fn merkle_path(
+    entry: &str,
+    leaf: &[u64; 4],
+    siblings: &[[u64; 4]],
+) -> Result<(MerklePath, RpoDigest), AdviceError> {
+    let nodes = siblings
+        .iter()
+        .map(|sibling| word_felts(entry, sibling).map(RpoDigest::from))
+        .collect::<Result<Vec<_>, _>>()?;
+    if nodes.len() > u8::MAX.into() {
+        return Err(AdviceError::InvalidMerklePath {
+            entry: entry.to_string(),
+            err: MerkleError::DepthTooBig(nodes.len() as u64),
+        });
+    }
+
+    Ok((
+        MerklePath::new(nodes),
+        RpoDigest::from(word_felts(entry, leaf)?),
+    ))
+}
+
+/// Packs `bytes` as their length followed by little-endian `u32` chunks.
+fn pack_bytes(bytes: &[u8]) -> Vec<Felt> {
+    let mut elements = vec![Felt::new(bytes.len() as u64)];
+    elements.extend(bytes.chunks(4).map(|chunk| {
+        let mut limb = [0u8; 4];
+        limb[..chunk.len()].copy_from_slice(chunk);
+        Felt::new(u32::from_le_bytes(limb).into())
+    }));
+    elements
+}
+
+fn format_word(word: Word) -> String {
+    let [a, b, c, d] = word.map(|felt| felt.as_int());
+    format!("{a}.{b}.{c}.{d}")
+}
+
+fn deserialize_hex<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let hex = String::deserialize(deserializer)?;
+    let hex = hex.strip_prefix("0x").unwrap_or(&hex);
+    if let Some(invalid) = hex.chars().find(|c| !c.is_ascii_hexdigit()) {
+        return Err(serde::de::Error::custom(format!(
+            "invalid hex character `{invalid}`"
+        )));
+    }
+    if hex.len() % 2 != 0 {
+        return Err(serde::de::Error::custom("hex string has an odd length"));
+    }
+    // Only ASCII hex digits are left, so every byte index is a char boundary
+    Ok((0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).expect("checked hex digits"))
+        .collect())
+}
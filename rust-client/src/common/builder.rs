@@ -0,0 +1,193 @@
+use miden_client::{
+    auth::TransactionAuthenticator,
+    crypto::RpoRandomCoin,
+    rpc::{NodeRpcClient, TonicRpcClient},
+    store::{sqlite_store::SqliteStore, Store, StoreAuthenticator},
+    transactions::{LocalTransactionProver, ProvingOptions, TransactionProver},
+    Client, ClientError, Felt,
+};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::sync::Arc;
+
+use super::{ClientConfig, ClientSetupError, FilesystemKeyStore, KeyStoreAuthenticator};
+
+/// Builds a `Client`, letting callers replace each of its parts.
+///
+/// Every part that is not set explicitly falls back to what [`initialize_client`] uses: a
+/// `SqliteStore` and `TonicRpcClient` from the selected profile of `miden-client.toml`, a
+/// `StoreAuthenticator` (backed by the keystore if one is configured), a
+/// `LocalTransactionProver` with default options, a random RNG seed and debug mode enabled.
+/// The config file is only loaded if one of these defaults needs it.
+///
+/// [`initialize_client`]: super::initialize_client
+pub struct ClientBuilder {
+    config: Option<ClientConfig>,
+    store: Option<Arc<dyn Store>>,
+    rpc_api: Option<Box<dyn NodeRpcClient + Send>>,
+    tx_prover: Option<Arc<dyn TransactionProver>>,
+    proving_options: ProvingOptions,
+    authenticator: Option<Arc<dyn TransactionAuthenticator>>,
+    rng_seed: Option<[u64; 4]>,
+    in_debug_mode: bool,
+}
+
+impl Default for ClientBuilder {
+    fn default() -> Self {
+        Self {
+            config: None,
+            store: None,
+            rpc_api: None,
+            tx_prover: None,
+            proving_options: ProvingOptions::default(),
+            authenticator: None,
+            rng_seed: None,
+            in_debug_mode: true,
+        }
+    }
+}
+
+impl ClientBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Uses `config` instead of loading `miden-client.toml`.
+    pub fn config(mut self, config: ClientConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Uses `store` instead of a `SqliteStore` created from the config.
+    pub fn store(mut self, store: Arc<dyn Store>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Uses `rpc_api` instead of a `TonicRpcClient` created from the config.
+    pub fn rpc_api(mut self, rpc_api: Box<dyn NodeRpcClient + Send>) -> Self {
+        self.rpc_api = Some(rpc_api);
+        self
+    }
+
+    /// Uses `tx_prover` (e.g. a remote prover) instead of a `LocalTransactionProver`.
+    pub fn tx_prover(mut self, tx_prover: Arc<dyn TransactionProver>) -> Self {
+        self.tx_prover = Some(tx_prover);
+        self
+    }
+
+    /// Sets the options of the default `LocalTransactionProver`. Ignored if a prover is set
+    /// with [`ClientBuilder::tx_prover`].
+    pub fn proving_options(mut self, proving_options: ProvingOptions) -> Self {
+        self.proving_options = proving_options;
+        self
+    }
+
+    /// Uses `authenticator` instead of the store (or keystore) authenticator.
+    pub fn authenticator(mut self, authenticator: Arc<dyn TransactionAuthenticator>) -> Self {
+        self.authenticator = Some(authenticator);
+        self
+    }
+
+    /// Seeds the client and authenticator RNGs with `seed`, making note serial numbers
+    /// reproducible. Meant for tests.
+    pub fn rng_seed(mut self, seed: [u64; 4]) -> Self {
+        self.rng_seed = Some(seed);
+        self
+    }
+
+    /// Enables or disables debug mode of the transaction executor (enabled by default).
+    pub fn in_debug_mode(mut self, in_debug_mode: bool) -> Self {
+        self.in_debug_mode = in_debug_mode;
+        self
+    }
+
+    /// Builds the `Client`.
+    pub async fn build(self) -> Result<Client<RpoRandomCoin>, ClientSetupError> {
+        Ok(self.build_with_authenticator().await?.0)
+    }
+
+    /// Builds the `Client` and returns it with its authenticator, which [`dry_run`] needs to
+    /// sign the transactions it executes again.
+    ///
+    /// [`dry_run`]: super::dry_run
+    pub async fn build_with_authenticator(
+        self,
+    ) -> Result<(Client<RpoRandomCoin>, Arc<dyn TransactionAuthenticator>), ClientSetupError> {
+        let needs_config =
+            self.store.is_none() || self.rpc_api.is_none() || self.authenticator.is_none();
+        let config = match self.config {
+            Some(config) => Some(config),
+            None if needs_config => Some(ClientConfig::load()?),
+            None => None,
+        };
+
+        let store = match self.store {
+            Some(store) => store,
+            None => {
+                let config = config
+                    .as_ref()
+                    .expect("config is loaded when store is not set");
+                let store = SqliteStore::new(&config.store)
+                    .await
+                    .map_err(ClientError::StoreError)?;
+                Arc::new(store)
+            }
+        };
+
+        let coin_seed: [u64; 4] = self.rng_seed.unwrap_or_else(|| rand::thread_rng().gen());
+        let rng_for_client = RpoRandomCoin::new(coin_seed.map(Felt::new));
+
+        let authenticator = match self.authenticator {
+            Some(authenticator) => authenticator,
+            None => {
+                let config = config
+                    .as_ref()
+                    .expect("config is loaded when authenticator is not set");
+                let rng_for_auth = RpoRandomCoin::new(coin_seed.map(Felt::new));
+                let store_authenticator = Arc::new(StoreAuthenticator::new_with_rng(
+                    store.clone(),
+                    rng_for_auth,
+                ));
+                match &config.keystore {
+                    Some(keystore_config) => {
+                        let keystore = FilesystemKeyStore::new(&keystore_config.path)?;
+                        Arc::new(
+                            KeyStoreAuthenticator::new_with_rng(
+                                keystore,
+                                keystore_config.password()?,
+                                StdRng::from_entropy(),
+                            )
+                            .with_fallback(store_authenticator),
+                        ) as Arc<dyn TransactionAuthenticator>
+                    }
+                    None => store_authenticator,
+                }
+            }
+        };
+
+        let tx_prover = self
+            .tx_prover
+            .unwrap_or_else(|| Arc::new(LocalTransactionProver::new(self.proving_options)));
+
+        let rpc_api = match self.rpc_api {
+            Some(rpc_api) => rpc_api,
+            None => {
+                let config = config
+                    .as_ref()
+                    .expect("config is loaded when rpc api is not set");
+                Box::new(TonicRpcClient::new(&config.rpc))
+            }
+        };
+
+        let client = Client::new(
+            rpc_api,
+            rng_for_client,
+            store,
+            authenticator.clone(),
+            tx_prover,
+            self.in_debug_mode,
+        );
+        Ok((client, authenticator))
+    }
+}
@@ -0,0 +1,197 @@
+use miden_client::{
+    transactions::TransactionScript,
+    utils::{Deserializable, Serializable},
+    Felt, Word,
+};
+use miden_lib::{transaction::TransactionKernel, MidenLib, StdLibrary};
+use miden_objects::{assembly::Library, crypto::hash::rpo::Rpo256, notes::NoteScript, vm::Program};
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use super::{
+    create_library,
+    library::{assemble_program, ASSEMBLER_DEBUG_MODE},
+    LibraryError,
+};
+
+/// Environment variable overriding the directory of [`MastCache::from_env`].
+pub const MAST_CACHE_DIR_ENV_VAR: &str = "MIDEN_MAST_CACHE_DIR";
+
+/// Assembler the cached artifacts are built with. Bump it together with the `miden-assembly`
+/// dependency so artifacts of an older assembler are not reused. The libraries the assembler
+/// links by default are part of the key through their digests, see [`linked_libraries_digests`].
+const ASSEMBLER_ID: &str = "miden-assembly-0.11";
+
+/// Extension of cached contract libraries.
+const LIBRARY_EXTENSION: &str = "masl";
+
+/// Extension of cached script programs.
+const PROGRAM_EXTENSION: &str = "masp";
+
+/// An on-disk cache of assembled MASM, so repeated runs skip reassembly.
+///
+/// Artifacts are stored as `<key>.masl` (libraries) and `<key>.masp` (programs), where the key
+/// is the hash of the source, the library path, the digests of the linked libraries, including
+/// the transaction kernel, `miden-lib` and the standard library, and the assembler settings.
+/// Changing any of these produces a new key, so stale entries are never returned. The files
+/// are plain serialized libraries and programs: a cache directory can be shared with teammates
+/// as a package of precompiled contracts.
+///
+/// The cache is best effort: unreadable entries are reassembled and failures to write an entry
+/// are ignored.
+#[derive(Clone, Debug)]
+pub struct MastCache {
+    dir: PathBuf,
+}
+
+impl MastCache {
+    /// Returns a cache stored in `dir`, created on first write.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Returns the cache in `$MIDEN_MAST_CACHE_DIR`, or in `miden/mast` under
+    /// `$XDG_CACHE_HOME` (`~/.cache` if unset).
+    pub fn from_env() -> Self {
+        let dir = env::var_os(MAST_CACHE_DIR_ENV_VAR)
+            .map(PathBuf::from)
+            .or_else(|| {
+                env::var_os("XDG_CACHE_HOME")
+                    .map(PathBuf::from)
+                    .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))
+                    .map(|cache_home| cache_home.join("miden").join("mast"))
+            })
+            .unwrap_or_else(|| env::temp_dir().join("miden-mast"));
+
+        Self::new(dir)
+    }
+
+    /// Returns the directory of the cache.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Like [`create_library`], but returns the cached library if `source` was already
+    /// assembled under `library_path`.
+    pub fn library(&self, library_path: &str, source: &str) -> Result<Library, LibraryError> {
+        let key = cache_key(&["library", library_path, source], &[]);
+        let path = self.entry_path(&key, LIBRARY_EXTENSION);
+        if let Some(library) = read_entry::<Library>(&path) {
+            return Ok(library);
+        }
+
+        let library = create_library(library_path, source)?;
+        self.write_entry(&path, &library.to_bytes());
+        Ok(library)
+    }
+
+    /// Like [`compile_tx_script_with_libraries`](super::compile_tx_script_with_libraries), but
+    /// reuses the cached program.
+    pub fn tx_script<T>(
+        &self,
+        inputs: T,
+        program: &str,
+        libraries: &[Library],
+    ) -> Result<TransactionScript, LibraryError>
+    where
+        T: IntoIterator<Item = (Word, Vec<Felt>)>,
+    {
+        Ok(TransactionScript::new(
+            self.program(program, libraries)?,
+            inputs,
+        ))
+    }
+
+    /// Like [`compile_note_script_with_libraries`](super::compile_note_script_with_libraries),
+    /// but reuses the cached program.
+    pub fn note_script(
+        &self,
+        program: &str,
+        libraries: &[Library],
+    ) -> Result<NoteScript, LibraryError> {
+        Ok(NoteScript::new(self.program(program, libraries)?))
+    }
+
+    /// Removes every entry of the cache.
+    pub fn clear(&self) -> std::io::Result<()> {
+        match fs::remove_dir_all(&self.dir) {
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
+    }
+
+    fn program(&self, program: &str, libraries: &[Library]) -> Result<Program, LibraryError> {
+        let key = cache_key(&["program", program], libraries);
+        let path = self.entry_path(&key, PROGRAM_EXTENSION);
+        if let Some(program) = read_entry::<Program>(&path) {
+            return Ok(program);
+        }
+
+        let program = assemble_program(program, libraries)?;
+        self.write_entry(&path, &program.to_bytes());
+        Ok(program)
+    }
+
+    fn entry_path(&self, key: &str, extension: &str) -> PathBuf {
+        self.dir.join(format!("{key}.{extension}"))
+    }
+
+    /// Writes the entry to a temporary file first, so concurrent runs never read a partially
+    /// written entry.
+    fn write_entry(&self, path: &Path, bytes: &[u8]) {
+        let tmp_path = path.with_extension(format!("tmp-{}", std::process::id()));
+        let written = fs::create_dir_all(&self.dir)
+            .and_then(|_| fs::write(&tmp_path, bytes))
+            .and_then(|_| fs::rename(&tmp_path, path));
+        if written.is_err() {
+            let _ = fs::remove_file(&tmp_path);
+        }
+    }
+}
+
+/// Hashes the assembler settings, `parts` and the digests of `libraries` into a hex key.
+fn cache_key(parts: &[&str], libraries: &[Library]) -> String {
+    let settings = if ASSEMBLER_DEBUG_MODE {
+        "debug"
+    } else {
+        "release"
+    };
+    let mut input = Vec::new();
+    for part in [ASSEMBLER_ID, settings].iter().chain(parts) {
+        input.extend_from_slice(&(part.len() as u64).to_le_bytes());
+        input.extend_from_slice(part.as_bytes());
+    }
+    input.extend_from_slice(linked_libraries_digests());
+    for library in libraries {
+        input.extend_from_slice(&library.digest().as_bytes());
+    }
+
+    Rpo256::hash(&input)
+        .to_hex()
+        .trim_start_matches("0x")
+        .to_string()
+}
+
+/// Returns the digests of the transaction kernel, `miden-lib` and the standard library, which
+/// `TransactionKernel::assembler` links into every artifact.
+fn linked_libraries_digests() -> &'static [u8] {
+    static DIGESTS: OnceLock<Vec<u8>> = OnceLock::new();
+    DIGESTS.get_or_init(|| {
+        let kernel = TransactionKernel::kernel();
+        let miden_lib = MidenLib::default();
+        let std_lib = StdLibrary::default();
+        [kernel.as_ref(), miden_lib.as_ref(), std_lib.as_ref()]
+            .into_iter()
+            .flat_map(|library: &Library| library.digest().as_bytes())
+            .collect()
+    })
+}
+
+fn read_entry<T: Deserializable>(path: &Path) -> Option<T> {
+    let bytes = fs::read(path).ok()?;
+    T::read_from_bytes(&bytes).ok()
+}
@@ -0,0 +1,304 @@
+use miden_client::{
+    accounts::AccountId,
+    crypto::RpoRandomCoin,
+    transactions::{TransactionRequest, TransactionRequestError, TransactionScript},
+    Client, Felt, Word,
+};
+use miden_objects::assets::Asset;
+
+use std::fmt;
+
+use super::{
+    compile_tx_script_with_libraries, submit_with_retry, AdviceData, AdviceError, AdviceValue,
+    ContractArtifact, LibraryError, MastCache, ProcedureError, RetryPolicy, SubmitError,
+    SubmitOutcome,
+};
+
+/// Most stack elements a procedure can receive, the depth of the stack visible to a `call`.
+const MAX_ARG_ELEMENTS: usize = 16;
+
+/// Errors that can occur while building a [`ProcedureCall`].
+#[derive(Debug)]
+pub enum CallError {
+    /// The contract does not export the procedure.
+    Procedure(ProcedureError),
+    /// The arguments take more stack elements than a called procedure can see.
+    TooManyArguments { procedure: String, elements: usize },
+    /// The generated script could not be compiled.
+    Library(LibraryError),
+    /// The advice passed to the procedure is invalid.
+    Advice(AdviceError),
+    /// The transaction request could not be built.
+    Request(TransactionRequestError),
+    /// The transaction could not be executed or submitted.
+    Submit(SubmitError),
+}
+
+impl fmt::Display for CallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CallError::Procedure(err) => err.fmt(f),
+            CallError::TooManyArguments {
+                procedure,
+                elements,
+            } => write!(
+                f,
+                "arguments of `{procedure}` take {elements} stack elements, at most \
+                 {MAX_ARG_ELEMENTS} are passed to a called procedure"
+            ),
+            CallError::Library(err) => err.fmt(f),
+            CallError::Advice(err) => err.fmt(f),
+            CallError::Request(err) => write!(f, "failed to build transaction request: {err}"),
+            CallError::Submit(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for CallError {}
+
+impl From<CallError> for String {
+    fn from(err: CallError) -> String {
+        err.to_string()
+    }
+}
+
+impl From<ProcedureError> for CallError {
+    fn from(err: ProcedureError) -> Self {
+        Self::Procedure(err)
+    }
+}
+
+impl From<LibraryError> for CallError {
+    fn from(err: LibraryError) -> Self {
+        Self::Library(err)
+    }
+}
+
+impl From<AdviceError> for CallError {
+    fn from(err: AdviceError) -> Self {
+        Self::Advice(err)
+    }
+}
+
+impl From<TransactionRequestError> for CallError {
+    fn from(err: TransactionRequestError) -> Self {
+        Self::Request(err)
+    }
+}
+
+impl From<SubmitError> for CallError {
+    fn from(err: SubmitError) -> Self {
+        Self::Submit(err)
+    }
+}
+
+/// A typed argument of a procedure, pushed onto the operand stack before the call.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StackArg {
+    /// A single element.
+    Felt(Felt),
+    /// Four elements, `[w3, w2, w1, w0]` from the top of the stack as `push.w0.w1.w2.w3`
+    /// leaves them, the layout `exec.account::get_item` returns.
+    Word(Word),
+    /// An account ID, as a single element.
+    AccountId(AccountId),
+    /// An asset, as the word it is encoded as, e.g. the `ASSET` input of `receive_asset`.
+    Asset(Asset),
+}
+
+impl StackArg {
+    /// Returns the number of stack elements the argument takes.
+    pub fn num_elements(&self) -> usize {
+        match self {
+            StackArg::Felt(_) | StackArg::AccountId(_) => 1,
+            StackArg::Word(_) | StackArg::Asset(_) => 4,
+        }
+    }
+
+    /// Returns the `push` instruction leaving the argument on top of the stack.
+    pub fn masm_push(&self) -> String {
+        let word = match self {
+            StackArg::Felt(felt) => return format!("push.{}", felt.as_int()),
+            StackArg::AccountId(account_id) => return format!("push.{}", u64::from(*account_id)),
+            StackArg::Word(word) => *word,
+            StackArg::Asset(asset) => Word::from(*asset),
+        };
+        let [a, b, c, d] = word.map(|felt| felt.as_int());
+        format!("push.{a}.{b}.{c}.{d}")
+    }
+}
+
+impl From<Felt> for StackArg {
+    fn from(felt: Felt) -> Self {
+        Self::Felt(felt)
+    }
+}
+
+impl From<Word> for StackArg {
+    fn from(word: Word) -> Self {
+        Self::Word(word)
+    }
+}
+
+impl From<AccountId> for StackArg {
+    fn from(account_id: AccountId) -> Self {
+        Self::AccountId(account_id)
+    }
+}
+
+impl From<Asset> for StackArg {
+    fn from(asset: Asset) -> Self {
+        Self::Asset(asset)
+    }
+}
+
+/// A transaction script calling one procedure of a contract with typed arguments.
+///
+/// Arguments are given in the order of the procedure's `Inputs: [...]` comment, the first one
+/// ending up on top of the stack, so
+///
+/// ```ignore
+/// accounts::MAP_COUNTER
+///     .call("increment_count_of")?
+///     .arg(account_id)
+///     .tx_script()?
+/// ```
+///
+/// builds `push.<account_id> call.map_counter_contract::increment_count_of`. The stack is
+/// truncated back to 16 elements after the call.
+#[derive(Clone, Debug)]
+pub struct ProcedureCall {
+    contract: ContractArtifact,
+    procedure: String,
+    args: Vec<StackArg>,
+    advice: Vec<AdviceData>,
+    cache: Option<MastCache>,
+}
+
+impl ProcedureCall {
+    /// Starts a call to `procedure` of `contract`. The script is assembled through
+    /// [`MastCache::from_env`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the contract does not export the procedure.
+    pub fn new(contract: ContractArtifact, procedure: &str) -> Result<Self, CallError> {
+        contract.procedure_roots().get(procedure)?;
+
+        Ok(Self {
+            contract,
+            procedure: procedure.to_string(),
+            args: Vec::new(),
+            advice: Vec::new(),
+            cache: Some(MastCache::from_env()),
+        })
+    }
+
+    /// Assembles the script through `cache` instead of the default cache.
+    pub fn with_cache(mut self, cache: MastCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Assembles the script every time instead of reusing cached programs.
+    pub fn without_cache(mut self) -> Self {
+        self.cache = None;
+        self
+    }
+
+    /// Adds the next argument.
+    pub fn arg(mut self, arg: impl Into<StackArg>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Adds the next arguments, in order.
+    pub fn args<A: Into<StackArg>>(mut self, args: impl IntoIterator<Item = A>) -> Self {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Moves the advice map values of `advice` to the advice stack before the call, so the
+    /// procedure reads them with `adv_push` in the order of the entries. Merkle paths stay in
+    /// the Merkle store.
+    ///
+    /// [`ProcedureCall::request`] provides the advice, scripts built with
+    /// [`ProcedureCall::tx_script`] need it provided with [`AdviceData::apply`].
+    pub fn advice(mut self, advice: &AdviceData) -> Self {
+        self.advice.push(advice.clone());
+        self
+    }
+
+    /// Returns the MASM source of the script.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the arguments take more than 16 stack elements.
+    pub fn source(&self) -> Result<String, CallError> {
+        let elements = self.args.iter().map(StackArg::num_elements).sum();
+        if elements > MAX_ARG_ELEMENTS {
+            return Err(CallError::TooManyArguments {
+                procedure: self.procedure.clone(),
+                elements,
+            });
+        }
+        let library_path = self.contract.library_path;
+        let module = library_path.rsplit("::").next().unwrap_or(library_path);
+
+        let mut source = format!("use.{library_path}\nuse.std::sys\n\nbegin\n");
+        let staged = self
+            .advice
+            .iter()
+            .rev()
+            .flat_map(|advice| advice.entries().iter().rev())
+            .filter(|entry| !matches!(entry.value(), AdviceValue::MerklePath { .. }));
+        for entry in staged {
+            source.push_str(&format!("    {}\n", entry.masm_stage()));
+        }
+        for arg in self.args.iter().rev() {
+            source.push_str(&format!("    {}\n", arg.masm_push()));
+        }
+        source.push_str(&format!(
+            "    call.{module}::{}\n    exec.sys::truncate_stack\nend\n",
+            self.procedure
+        ));
+
+        Ok(source)
+    }
+
+    /// Compiles the script, linked against the contract library, or returns the cached program
+    /// of the same script.
+    pub fn tx_script(&self) -> Result<TransactionScript, CallError> {
+        let source = self.source()?;
+        let libraries = [self.contract.library()];
+        let tx_script = match &self.cache {
+            Some(cache) => cache.tx_script(vec![], &source, &libraries)?,
+            None => compile_tx_script_with_libraries(vec![], &source, &libraries)?,
+        };
+
+        Ok(tx_script)
+    }
+
+    /// Returns a transaction request running the script, with the advice of the call.
+    pub fn request(&self) -> Result<TransactionRequest, CallError> {
+        let request = self
+            .advice
+            .iter()
+            .fold(TransactionRequest::new(), |request, advice| {
+                advice.apply(request)
+            });
+
+        Ok(request.with_custom_script(self.tx_script()?)?)
+    }
+
+    /// Executes the call against the account `account_id` and submits the transaction,
+    /// retrying with the default [`RetryPolicy`], see [`submit_with_retry`].
+    pub async fn submit(
+        &self,
+        client: &mut Client<RpoRandomCoin>,
+        account_id: AccountId,
+    ) -> Result<SubmitOutcome, CallError> {
+        let request = self.request()?;
+        Ok(submit_with_retry(client, account_id, request, RetryPolicy::default()).await?)
+    }
+}
@@ -0,0 +1,266 @@
+use miden_client::{config::RpcConfig, store::sqlite_store::config::SqliteStoreConfig};
+
+use figment::{
+    providers::{Env, Format, Toml},
+    Figment, Profile, Provider,
+};
+use serde::Deserialize;
+use std::{
+    env, fmt,
+    fs::{self, OpenOptions},
+    io,
+    path::{Path, PathBuf},
+};
+
+/// Name of your local TOML file containing client config.
+/// Adjust this if you store it in another place/name.
+pub const CLIENT_CONFIG_FILE_NAME: &str = "miden-client.toml";
+
+/// Profile used when none is selected via `--profile` or `MIDEN_PROFILE`.
+const DEFAULT_PROFILE: &str = "testnet";
+
+/// Environment variable used to select the network profile.
+const PROFILE_ENV_VAR: &str = "MIDEN_PROFILE";
+
+/// Prefix of the environment variables overriding values from the TOML file.
+/// Nested keys are separated by `__`, e.g. `MIDEN_RPC__ENDPOINT__HOST`.
+const ENV_OVERRIDE_PREFIX: &str = "MIDEN_";
+
+/// Environment variable holding the password of the keystore configured under `keystore`.
+pub const KEYSTORE_PASSWORD_ENV_VAR: &str = "MIDEN_KEYSTORE_PASSWORD";
+
+/// Protocols accepted for the RPC endpoint.
+const SUPPORTED_PROTOCOLS: [&str; 2] = ["http", "https"];
+
+/// Errors that can occur while locating, parsing or validating the client config.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// No config file was found in any of the searched locations.
+    NotFound(Vec<PathBuf>),
+    /// The config file is not valid TOML or does not match the expected structure.
+    Malformed(PathBuf, Box<figment::Error>),
+    /// The selected profile is not defined in the config file.
+    UnknownProfile {
+        profile: String,
+        available: Vec<String>,
+    },
+    /// The RPC endpoint host or port is invalid.
+    InvalidEndpoint(String),
+    /// The RPC endpoint uses a protocol other than `http` or `https`.
+    UnsupportedProtocol(String),
+    /// The store database file cannot be created or written to.
+    StoreNotWritable(PathBuf, io::Error),
+    /// A keystore is configured but its password is not set in the environment.
+    MissingKeyStorePassword,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::NotFound(searched) => {
+                let searched: Vec<_> = searched.iter().map(|p| p.display().to_string()).collect();
+                write!(
+                    f,
+                    "could not find {CLIENT_CONFIG_FILE_NAME}, searched: {}",
+                    searched.join(", ")
+                )
+            }
+            ConfigError::Malformed(path, err) => {
+                write!(f, "failed to parse {}: {err}", path.display())
+            }
+            ConfigError::UnknownProfile { profile, available } => write!(
+                f,
+                "profile `{profile}` is not defined, available profiles: {}",
+                available.join(", ")
+            ),
+            ConfigError::InvalidEndpoint(err) => write!(f, "invalid RPC endpoint: {err}"),
+            ConfigError::UnsupportedProtocol(protocol) => write!(
+                f,
+                "unsupported RPC protocol `{protocol}`, expected one of: {}",
+                SUPPORTED_PROTOCOLS.join(", ")
+            ),
+            ConfigError::StoreNotWritable(path, err) => {
+                write!(f, "store path {} is not writable: {err}", path.display())
+            }
+            ConfigError::MissingKeyStorePassword => write!(
+                f,
+                "a keystore is configured but {KEYSTORE_PASSWORD_ENV_VAR} is not set"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Simple container for everything in your TOML file (RPC + store configs, etc.)
+#[derive(Debug, Deserialize)]
+pub struct ClientConfig {
+    /// Describes settings related to the RPC endpoint
+    pub rpc: RpcConfig,
+    /// Describes settings related to the store.
+    pub store: SqliteStoreConfig,
+    /// Optional file-based keystore holding account secret keys.
+    #[serde(default)]
+    pub keystore: Option<KeyStoreConfig>,
+}
+
+/// Settings of the file-based keystore.
+#[derive(Debug, Deserialize)]
+pub struct KeyStoreConfig {
+    /// Directory holding the encrypted key files.
+    pub path: PathBuf,
+}
+
+impl KeyStoreConfig {
+    /// Returns the keystore password from the `MIDEN_KEYSTORE_PASSWORD` environment variable.
+    pub fn password(&self) -> Result<String, ConfigError> {
+        env::var(KEYSTORE_PASSWORD_ENV_VAR).map_err(|_| ConfigError::MissingKeyStorePassword)
+    }
+}
+
+impl ClientConfig {
+    /// Loads and validates the config for `profile` from the TOML file at `path`.
+    ///
+    /// Every top-level table of the file is a named profile (e.g. `testnet`, `localhost`,
+    /// `custom`), values under `default` apply to all profiles, and `MIDEN_` prefixed
+    /// environment variables are layered on top of the selected profile. Relative store and
+    /// keystore paths are resolved against the directory containing the config file.
+    pub fn from_file<P: AsRef<Path>>(path: P, profile: &str) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        if !path.is_file() {
+            return Err(ConfigError::NotFound(vec![path.to_path_buf()]));
+        }
+
+        let toml = Toml::file(path).nested();
+        let profiles = toml
+            .data()
+            .map_err(|err| ConfigError::Malformed(path.to_path_buf(), Box::new(err)))?;
+        let available: Vec<String> = profiles
+            .keys()
+            .filter(|p| **p != Profile::Default && **p != Profile::Global)
+            .map(|p| p.to_string())
+            .collect();
+        if !available.iter().any(|p| p.eq_ignore_ascii_case(profile)) {
+            return Err(ConfigError::UnknownProfile {
+                profile: profile.to_string(),
+                available,
+            });
+        }
+
+        let mut config: ClientConfig = Figment::from(toml)
+            .merge(
+                Env::prefixed(ENV_OVERRIDE_PREFIX)
+                    .ignore(&["profile", "keystore_password"])
+                    .split("__")
+                    .global(),
+            )
+            .select(profile)
+            .extract()
+            .map_err(|err| ConfigError::Malformed(path.to_path_buf(), Box::new(err)))?;
+
+        if let Some(config_dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            let store_path = Path::new(&config.store.database_filepath);
+            if store_path.is_relative() {
+                config.store.database_filepath =
+                    config_dir.join(store_path).to_string_lossy().into_owned();
+            }
+            if let Some(keystore) = config.keystore.as_mut() {
+                if keystore.path.is_relative() {
+                    keystore.path = config_dir.join(&keystore.path);
+                }
+            }
+        }
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Loads the config from the first `miden-client.toml` found in [`config_search_paths`],
+    /// using the profile selected by [`selected_profile`].
+    pub fn load() -> Result<Self, ConfigError> {
+        let searched = config_search_paths();
+        let path = searched
+            .iter()
+            .find(|path| path.is_file())
+            .ok_or_else(|| ConfigError::NotFound(searched.clone()))?;
+
+        Self::from_file(path, &selected_profile())
+    }
+
+    /// Checks that the endpoint is well formed and that the store database can be written.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let endpoint = &self.rpc.endpoint;
+        if !SUPPORTED_PROTOCOLS.contains(&endpoint.protocol()) {
+            return Err(ConfigError::UnsupportedProtocol(
+                endpoint.protocol().to_string(),
+            ));
+        }
+        let host = endpoint.host();
+        if host.is_empty() || host.contains("://") || host.contains(char::is_whitespace) {
+            return Err(ConfigError::InvalidEndpoint(format!(
+                "invalid host `{host}`"
+            )));
+        }
+        if endpoint.port() == 0 {
+            return Err(ConfigError::InvalidEndpoint(format!(
+                "invalid port 0 for host `{host}`"
+            )));
+        }
+
+        check_store_writable(Path::new(&self.store.database_filepath))
+    }
+}
+
+/// Returns the locations searched for `miden-client.toml`, in order: the current directory,
+/// the `rust-client` crate directory and `$XDG_CONFIG_HOME/miden` (or `~/.config/miden`).
+pub fn config_search_paths() -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    if let Ok(cwd) = env::current_dir() {
+        paths.push(cwd.join(CLIENT_CONFIG_FILE_NAME));
+    }
+    paths.push(Path::new(env!("CARGO_MANIFEST_DIR")).join(CLIENT_CONFIG_FILE_NAME));
+
+    let config_home = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")));
+    if let Some(config_home) = config_home {
+        paths.push(config_home.join("miden").join(CLIENT_CONFIG_FILE_NAME));
+    }
+
+    paths.dedup();
+    paths
+}
+
+/// Returns the network profile to use: the value of a `--profile <name>` (or
+/// `--profile=<name>`) command line flag, then the `MIDEN_PROFILE` environment variable,
+/// and finally `testnet`.
+pub fn selected_profile() -> String {
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--profile" {
+            if let Some(profile) = args.next() {
+                return profile;
+            }
+        } else if let Some(profile) = arg.strip_prefix("--profile=") {
+            return profile.to_string();
+        }
+    }
+
+    Profile::from_env_or(PROFILE_ENV_VAR, DEFAULT_PROFILE).to_string()
+}
+
+/// Checks that the store database at `path` can be opened for writing, or created if it does
+/// not exist yet. A probe file is removed again so that the store still initializes its schema.
+fn check_store_writable(path: &Path) -> Result<(), ConfigError> {
+    let result = if path.exists() {
+        OpenOptions::new().append(true).open(path).map(drop)
+    } else {
+        OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(path)
+            .and_then(|_| fs::remove_file(path))
+    };
+
+    result.map_err(|err| ConfigError::StoreNotWritable(path.to_path_buf(), err))
+}
@@ -0,0 +1,848 @@
+use miden_client::{
+    accounts::{AccountId, AccountType},
+    assets::FungibleAsset,
+    crypto::{FeltRng, RpoRandomCoin},
+    notes::{NoteId, NoteType},
+    transactions::{OutputNote, TransactionRequest, TransactionRequestError},
+    Client, ClientError, Felt,
+};
+use miden_lib::notes::create_p2id_note;
+use miden_objects::{AssetError, NoteError, MAX_OUTPUT_NOTES_PER_TX};
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+use tokio::time::sleep;
+
+use super::{submit_executed_with_retry, Backoff, RetryPolicy, SubmitError};
+
+/// Errors that can occur while dispersing tokens.
+#[derive(Debug)]
+pub enum DisperseError {
+    /// The CSV file or the journal could not be read or written.
+    Io(PathBuf, io::Error),
+    /// A row of the CSV file is invalid.
+    InvalidRow { line: usize, reason: String },
+    /// A record of the journal is invalid.
+    MalformedJournal {
+        path: PathBuf,
+        line: usize,
+        reason: String,
+    },
+    /// The journal records payments of another sender.
+    SenderMismatch {
+        path: PathBuf,
+        journal_sender: AccountId,
+        sender: AccountId,
+    },
+    /// A payment amount is not a valid amount of the faucet's asset.
+    Asset(AssetError),
+    /// A P2ID note could not be created.
+    Note(NoteError),
+    /// The transaction request could not be built.
+    Request(TransactionRequestError),
+    /// A transaction could not be executed, or the node could not be queried.
+    Client(Box<ClientError>),
+    /// A transaction could not be submitted. It is recorded as pending in the journal, and
+    /// resolved by the next run.
+    Submit(SubmitError),
+}
+
+impl fmt::Display for DisperseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DisperseError::Io(path, err) => write!(f, "failed to access {}: {err}", path.display()),
+            DisperseError::InvalidRow { line, reason } => write!(f, "invalid row {line}: {reason}"),
+            DisperseError::MalformedJournal { path, line, reason } => write!(
+                f,
+                "invalid record on line {line} of journal {}: {reason}",
+                path.display()
+            ),
+            DisperseError::SenderMismatch {
+                path,
+                journal_sender,
+                sender,
+            } => write!(
+                f,
+                "journal {} records payments of {}, not of {}, use another journal",
+                path.display(),
+                journal_sender.to_hex(),
+                sender.to_hex()
+            ),
+            DisperseError::Asset(err) => write!(f, "invalid payment amount: {err}"),
+            DisperseError::Note(err) => write!(f, "failed to create P2ID note: {err}"),
+            DisperseError::Request(err) => {
+                write!(f, "failed to build transaction request: {err}")
+            }
+            DisperseError::Client(err) => err.fmt(f),
+            DisperseError::Submit(err) => write!(f, "{err}, run again to resume"),
+        }
+    }
+}
+
+impl std::error::Error for DisperseError {}
+
+impl From<DisperseError> for String {
+    fn from(err: DisperseError) -> String {
+        err.to_string()
+    }
+}
+
+impl From<AssetError> for DisperseError {
+    fn from(err: AssetError) -> Self {
+        Self::Asset(err)
+    }
+}
+
+impl From<NoteError> for DisperseError {
+    fn from(err: NoteError) -> Self {
+        Self::Note(err)
+    }
+}
+
+impl From<TransactionRequestError> for DisperseError {
+    fn from(err: TransactionRequestError) -> Self {
+        Self::Request(err)
+    }
+}
+
+impl From<ClientError> for DisperseError {
+    fn from(err: ClientError) -> Self {
+        Self::Client(Box::new(err))
+    }
+}
+
+impl From<SubmitError> for DisperseError {
+    fn from(err: SubmitError) -> Self {
+        Self::Submit(err)
+    }
+}
+
+/// A row of a disperse CSV file: `amount` tokens of the fungible faucet `faucet_id` for
+/// `recipient`, paid with a P2ID note.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Payment {
+    /// Line of the row in the CSV file, starting at 1.
+    pub line: usize,
+    pub recipient: AccountId,
+    pub faucet_id: AccountId,
+    pub amount: u64,
+    /// Number of identical rows above this one, so repeated payments are told apart.
+    occurrence: u32,
+}
+
+impl Payment {
+    /// Identifies the payment in the journal. Line numbers are left out so that the rows of the
+    /// CSV file can be reordered between runs.
+    fn key(&self) -> PaymentKey {
+        PaymentKey {
+            recipient: self.recipient.to_hex(),
+            faucet_id: self.faucet_id.to_hex(),
+            amount: self.amount,
+            occurrence: self.occurrence,
+        }
+    }
+}
+
+/// Parses a disperse CSV file: one `recipient,faucet,amount` row per payment, account IDs in
+/// hex. Empty lines, lines starting with `#` and a leading `recipient,faucet,amount` header are
+/// skipped.
+///
+/// # Errors
+///
+/// Returns an error if a row does not have three columns, an account ID is invalid, the faucet
+/// is not a fungible faucet, or an amount is zero or not a number.
+pub fn parse_payments(csv: &str) -> Result<Vec<Payment>, DisperseError> {
+    let mut payments = Vec::new();
+    let mut occurrences: BTreeMap<(AccountId, AccountId, u64), u32> = BTreeMap::new();
+    let mut first_row = true;
+
+    for (index, row) in csv.lines().enumerate() {
+        let line = index + 1;
+        let row = row.trim();
+        if row.is_empty() || row.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = row.split(',').map(str::trim).collect();
+        let is_header = first_row && fields[0].eq_ignore_ascii_case("recipient");
+        first_row = false;
+        if is_header {
+            continue;
+        }
+
+        let invalid = |reason: String| DisperseError::InvalidRow { line, reason };
+        let [recipient, faucet_id, amount] = fields[..] else {
+            return Err(invalid(format!(
+                "expected 3 columns, found {}",
+                fields.len()
+            )));
+        };
+        let recipient = AccountId::from_hex(recipient)
+            .map_err(|err| invalid(format!("invalid recipient `{recipient}`: {err}")))?;
+        let faucet_id = AccountId::from_hex(faucet_id)
+            .map_err(|err| invalid(format!("invalid faucet `{faucet_id}`: {err}")))?;
+        if faucet_id.account_type() != AccountType::FungibleFaucet {
+            return Err(invalid(format!(
+                "{} is not a fungible faucet",
+                faucet_id.to_hex()
+            )));
+        }
+        let amount = match amount.parse::<u64>() {
+            Ok(0) => return Err(invalid("amount is zero".to_string())),
+            Ok(amount) => amount,
+            Err(err) => return Err(invalid(format!("invalid amount `{amount}`: {err}"))),
+        };
+
+        let occurrence = occurrences
+            .entry((recipient, faucet_id, amount))
+            .or_default();
+        payments.push(Payment {
+            line,
+            recipient,
+            faucet_id,
+            amount,
+            occurrence: *occurrence,
+        });
+        *occurrence += 1;
+    }
+
+    Ok(payments)
+}
+
+/// Reads and parses the disperse CSV file at `path`, see [`parse_payments`].
+pub fn read_payments(path: impl AsRef<Path>) -> Result<Vec<Payment>, DisperseError> {
+    let path = path.as_ref();
+    let csv = fs::read_to_string(path).map_err(|err| DisperseError::Io(path.to_path_buf(), err))?;
+    parse_payments(&csv)
+}
+
+/// Settings of [`disperse`].
+#[derive(Clone, Copy, Debug)]
+pub struct DisperseOptions {
+    /// Most P2ID notes created by one transaction, capped at the protocol limit.
+    pub max_notes_per_tx: usize,
+    pub note_type: NoteType,
+    /// Number of blocks after which a submitted transaction that was not committed expires.
+    /// A run resuming after a crash waits at most this long for a transaction that may still
+    /// be committed.
+    pub expiration_delta: u16,
+    /// Retries of transient submission failures, and delays between polls of the node.
+    pub retry: RetryPolicy,
+}
+
+impl Default for DisperseOptions {
+    fn default() -> Self {
+        Self {
+            max_notes_per_tx: MAX_OUTPUT_NOTES_PER_TX,
+            note_type: NoteType::Public,
+            expiration_delta: 20,
+            retry: RetryPolicy::default(),
+        }
+    }
+}
+
+impl DisperseOptions {
+    /// Returns the number of notes per transaction, `max_notes_per_tx` within the protocol
+    /// limit.
+    pub fn chunk_size(&self) -> usize {
+        self.max_notes_per_tx.clamp(1, MAX_OUTPUT_NOTES_PER_TX)
+    }
+}
+
+/// Outcome of a [`disperse`] run.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DisperseSummary {
+    /// Number of payments in the CSV file.
+    pub payments: usize,
+    /// Payments committed by earlier runs, including transactions pending when they stopped.
+    pub previously_paid: usize,
+    /// Payments committed by this run.
+    pub newly_paid: usize,
+    /// Payments of transactions that expired before being committed, sent again by the next
+    /// run.
+    pub unpaid: usize,
+    /// Number of transactions submitted by this run.
+    pub transactions: usize,
+}
+
+/// Returns the request paying `payments` with P2ID notes from the wallet `sender_id`, expiring
+/// after `options.expiration_delta` blocks. Serial numbers are drawn from `rng`.
+pub fn disperse_request(
+    sender_id: AccountId,
+    payments: &[Payment],
+    options: &DisperseOptions,
+    rng: &mut impl FeltRng,
+) -> Result<TransactionRequest, DisperseError> {
+    let mut output_notes = Vec::with_capacity(payments.len());
+    for payment in payments {
+        let asset = FungibleAsset::new(payment.faucet_id, payment.amount)?;
+        let note = create_p2id_note(
+            sender_id,
+            payment.recipient,
+            vec![asset.into()],
+            options.note_type,
+            Felt::new(0),
+            rng,
+        )?;
+        output_notes.push(OutputNote::Full(note));
+    }
+
+    Ok(TransactionRequest::new()
+        .with_own_output_notes(output_notes)?
+        .with_expiration_delta(options.expiration_delta)?)
+}
+
+/// Returns the payments of `payments` that are neither committed nor pending according to the
+/// journal of `sender_id` at `journal_path`, in order.
+pub fn unpaid_payments(
+    sender_id: AccountId,
+    payments: &[Payment],
+    journal_path: impl AsRef<Path>,
+) -> Result<Vec<Payment>, DisperseError> {
+    let journal = Journal::read(journal_path.as_ref(), sender_id)?;
+    let sent = journal.payments(|status| !matches!(status, BatchStatus::Expired));
+
+    Ok(payments
+        .iter()
+        .filter(|payment| !sent.contains(&payment.key()))
+        .cloned()
+        .collect())
+}
+
+/// Pays `payments` from the wallet `sender_id` with P2ID notes, in transactions of at most
+/// `options.max_notes_per_tx` notes, and records progress in the journal at `journal_path`.
+///
+/// Each transaction is recorded in the journal before it is submitted. A run resuming after a
+/// crash first waits until the transactions still pending are either committed, found by their
+/// output notes, or expired, and then only pays the payments not committed yet. A transaction
+/// that expired can never be committed, so no payment is made twice.
+///
+/// # Errors
+///
+/// Returns an error if the journal records payments of another sender, or if a transaction
+/// fails to execute or to be submitted. Run again with the same journal to resume.
+pub async fn disperse(
+    client: &mut Client<RpoRandomCoin>,
+    sender_id: AccountId,
+    payments: &[Payment],
+    journal_path: impl AsRef<Path>,
+    options: &DisperseOptions,
+) -> Result<DisperseSummary, DisperseError> {
+    let mut journal = Journal::read(journal_path.as_ref(), sender_id)?;
+    client.sync_state().await?;
+
+    for batch in journal.pending() {
+        let status = await_batch(client, &batch, options.retry.backoff).await?;
+        journal.append(batch.resolution(status))?;
+    }
+
+    let paid = journal.payments(|status| matches!(status, BatchStatus::Committed(_)));
+    let (previously_paid, remaining): (Vec<_>, Vec<_>) = payments
+        .iter()
+        .cloned()
+        .partition(|payment| paid.contains(&payment.key()));
+
+    let mut summary = DisperseSummary {
+        payments: payments.len(),
+        previously_paid: previously_paid.len(),
+        ..Default::default()
+    };
+    for chunk in remaining.chunks(options.chunk_size()) {
+        let request = disperse_request(sender_id, chunk, options, client.rng())?;
+        let tx_result = client.new_transaction(sender_id, request).await?;
+
+        let executed = tx_result.executed_transaction();
+        let batch = PendingBatch {
+            batch: journal.next_batch(),
+            tx_id: executed.id().to_string(),
+            expiration_block: executed.block_header().block_num()
+                + u32::from(options.expiration_delta),
+            note_ids: executed
+                .output_notes()
+                .iter()
+                .map(|note| note.id())
+                .collect(),
+            payments: chunk.iter().map(Payment::key).collect(),
+        };
+        journal.append(batch.record(sender_id))?;
+
+        submit_executed_with_retry(client, tx_result, options.retry).await?;
+        summary.transactions += 1;
+
+        let status = await_batch(client, &batch, options.retry.backoff).await?;
+        match status {
+            BatchStatus::Committed(_) => summary.newly_paid += chunk.len(),
+            _ => summary.unpaid += chunk.len(),
+        }
+        journal.append(batch.resolution(status))?;
+    }
+
+    Ok(summary)
+}
+
+/// Polls the node until the output notes of `batch` are committed, or the chain is past the
+/// block at which the transaction expires.
+async fn await_batch(
+    client: &mut Client<RpoRandomCoin>,
+    batch: &PendingBatch,
+    backoff: Backoff,
+) -> Result<BatchStatus, DisperseError> {
+    let mut delay = backoff.initial;
+    loop {
+        // The chain tip is read before the notes, so notes committed up to the tip are found
+        client.sync_state().await?;
+        let chain_tip = client.get_sync_height().await?;
+        let committed = client
+            .rpc_api()
+            .get_notes_by_id(&batch.note_ids)
+            .await
+            .map_err(ClientError::from)?;
+
+        if committed.len() == batch.note_ids.len() {
+            let block_num = committed
+                .iter()
+                .map(|note| note.inclusion_details().block_num)
+                .max()
+                .unwrap_or(chain_tip);
+            return Ok(BatchStatus::Committed(block_num));
+        }
+        if chain_tip > batch.expiration_block {
+            return Ok(BatchStatus::Expired);
+        }
+
+        sleep(delay).await;
+        delay = backoff.next(delay);
+    }
+}
+
+// JOURNAL
+// ================================================================================================
+
+/// A payment as recorded in the journal.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+struct PaymentKey {
+    recipient: String,
+    faucet_id: String,
+    amount: u64,
+    occurrence: u32,
+}
+
+/// A line of the journal.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum JournalRecord {
+    /// The transaction of the batch was executed and is about to be submitted.
+    Pending {
+        batch: u32,
+        sender: String,
+        tx_id: String,
+        expiration_block: u32,
+        note_ids: Vec<String>,
+        payments: Vec<PaymentKey>,
+    },
+    /// The output notes of the batch were committed.
+    Committed { batch: u32, block_num: u32 },
+    /// The transaction of the batch expired without being committed.
+    Expired { batch: u32 },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum BatchStatus {
+    Pending,
+    Committed(u32),
+    Expired,
+}
+
+#[derive(Clone, Debug)]
+struct PendingBatch {
+    batch: u32,
+    tx_id: String,
+    expiration_block: u32,
+    note_ids: Vec<NoteId>,
+    payments: Vec<PaymentKey>,
+}
+
+impl PendingBatch {
+    fn record(&self, sender_id: AccountId) -> JournalRecord {
+        JournalRecord::Pending {
+            batch: self.batch,
+            sender: sender_id.to_hex(),
+            tx_id: self.tx_id.clone(),
+            expiration_block: self.expiration_block,
+            note_ids: self.note_ids.iter().map(NoteId::to_hex).collect(),
+            payments: self.payments.clone(),
+        }
+    }
+
+    fn resolution(&self, status: BatchStatus) -> JournalRecord {
+        match status {
+            BatchStatus::Committed(block_num) => JournalRecord::Committed {
+                batch: self.batch,
+                block_num,
+            },
+            _ => JournalRecord::Expired { batch: self.batch },
+        }
+    }
+}
+
+/// Append-only log of the batches of a disperse run, one JSON record per line. Every batch
+/// records its sender, so the journal of one wallet is never resumed by another.
+struct Journal {
+    path: PathBuf,
+    /// Length of the journal up to its last complete record.
+    len: u64,
+    file: Option<File>,
+    batches: BTreeMap<u32, (PendingBatch, BatchStatus)>,
+}
+
+impl Journal {
+    /// Reads the journal of `sender_id` at `path`, empty if the file does not exist. A last
+    /// record cut short by a crash is dropped, its transaction was never submitted.
+    fn read(path: &Path, sender_id: AccountId) -> Result<Self, DisperseError> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(DisperseError::Io(path.to_path_buf(), err)),
+        };
+        let complete = &content[..content.rfind('\n').map_or(0, |end| end + 1)];
+
+        let mut journal = Self {
+            path: path.to_path_buf(),
+            len: complete.len() as u64,
+            file: None,
+            batches: BTreeMap::new(),
+        };
+        for (index, line) in complete.lines().enumerate() {
+            let malformed = |reason: String| DisperseError::MalformedJournal {
+                path: path.to_path_buf(),
+                line: index + 1,
+                reason,
+            };
+            let record = serde_json::from_str(line).map_err(|err| malformed(err.to_string()))?;
+            if let JournalRecord::Pending { sender, .. } = &record {
+                let journal_sender = AccountId::from_hex(sender)
+                    .map_err(|err| malformed(format!("sender {sender}: {err}")))?;
+                if journal_sender != sender_id {
+                    return Err(DisperseError::SenderMismatch {
+                        path: path.to_path_buf(),
+                        journal_sender,
+                        sender: sender_id,
+                    });
+                }
+            }
+            journal.apply(record).map_err(malformed)?;
+        }
+
+        Ok(journal)
+    }
+
+    fn apply(&mut self, record: JournalRecord) -> Result<(), String> {
+        let (batch, status) = match record {
+            JournalRecord::Pending {
+                batch,
+                sender: _,
+                tx_id,
+                expiration_block,
+                note_ids,
+                payments,
+            } => {
+                let note_ids = note_ids
+                    .iter()
+                    .map(|id| NoteId::try_from_hex(id).map_err(|err| format!("note {id}: {err}")))
+                    .collect::<Result<_, _>>()?;
+                let pending = PendingBatch {
+                    batch,
+                    tx_id,
+                    expiration_block,
+                    note_ids,
+                    payments,
+                };
+                self.batches.insert(batch, (pending, BatchStatus::Pending));
+                return Ok(());
+            }
+            JournalRecord::Committed { batch, block_num } => {
+                (batch, BatchStatus::Committed(block_num))
+            }
+            JournalRecord::Expired { batch } => (batch, BatchStatus::Expired),
+        };
+
+        match self.batches.get_mut(&batch) {
+            Some((_, current)) => {
+                *current = status;
+                Ok(())
+            }
+            None => Err(format!("batch {batch} was never recorded as pending")),
+        }
+    }
+
+    /// Appends `record` and flushes it to disk before returning.
+    fn append(&mut self, record: JournalRecord) -> Result<(), DisperseError> {
+        let io_error = |err| DisperseError::Io(self.path.clone(), err);
+        if self.file.is_none() {
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)
+                .map_err(io_error)?;
+            file.set_len(self.len).map_err(io_error)?;
+            self.file = Some(file);
+        }
+
+        let mut line = serde_json::to_string(&record).expect("journal records serialize to JSON");
+        line.push('\n');
+        let file = self.file.as_mut().expect("journal file was opened");
+        file.write_all(line.as_bytes())
+            .and_then(|()| file.sync_data())
+            .map_err(io_error)?;
+        self.len += line.len() as u64;
+
+        self.apply(record)
+            .expect("resolutions are appended after their pending record");
+        Ok(())
+    }
+
+    fn next_batch(&self) -> u32 {
+        self.batches.keys().next_back().map_or(0, |batch| batch + 1)
+    }
+
+    fn pending(&self) -> Vec<PendingBatch> {
+        self.batches
+            .values()
+            .filter(|(_, status)| *status == BatchStatus::Pending)
+            .map(|(batch, _)| batch.clone())
+            .collect()
+    }
+
+    /// Returns the payments of the batches whose status matches `filter`.
+    fn payments(&self, filter: impl Fn(BatchStatus) -> bool) -> BTreeSet<PaymentKey> {
+        self.batches
+            .values()
+            .filter(|(_, status)| filter(*status))
+            .flat_map(|(batch, _)| batch.payments.iter().cloned())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use miden_client::{accounts::AccountStorageMode, Word};
+
+    fn wallet(seed: u8) -> AccountId {
+        AccountId::new_with_type_and_mode(
+            [seed; 8],
+            AccountType::RegularAccountUpdatableCode,
+            AccountStorageMode::Public,
+        )
+    }
+
+    fn faucet() -> AccountId {
+        AccountId::new_with_type_and_mode(
+            [9; 8],
+            AccountType::FungibleFaucet,
+            AccountStorageMode::Public,
+        )
+    }
+
+    fn row(recipient: AccountId, amount: u64) -> String {
+        format!("{},{},{amount}", recipient.to_hex(), faucet().to_hex())
+    }
+
+    /// Returns a journal path unique to `test`, without any file at it.
+    fn journal_path(test: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("disperse-{test}-{}.journal", std::process::id()));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    fn pending_batch(journal: &Journal, payments: &[Payment]) -> PendingBatch {
+        let batch = journal.next_batch();
+        PendingBatch {
+            batch,
+            tx_id: format!("tx-{batch}"),
+            expiration_block: 100,
+            note_ids: vec![NoteId::from(Word::from([Felt::new(batch.into()); 4]))],
+            payments: payments.iter().map(Payment::key).collect(),
+        }
+    }
+
+    #[test]
+    fn parse_payments_skips_header_comments_and_blank_lines() {
+        let csv = format!(
+            "recipient,faucet,amount\n\n# first payment\n{}\n  {}  \n",
+            row(wallet(1), 10),
+            row(wallet(2), 20)
+        );
+        let payments = parse_payments(&csv).unwrap();
+
+        assert_eq!(payments.len(), 2);
+        assert_eq!(payments[0].line, 4);
+        assert_eq!(payments[0].recipient, wallet(1));
+        assert_eq!(payments[0].faucet_id, faucet());
+        assert_eq!(payments[0].amount, 10);
+        assert_eq!(payments[1].line, 5);
+        assert_eq!(payments[1].amount, 20);
+    }
+
+    #[test]
+    fn parse_payments_counts_identical_rows() {
+        let csv = [
+            row(wallet(1), 10),
+            row(wallet(2), 10),
+            row(wallet(1), 10),
+            row(wallet(1), 11),
+        ]
+        .join("\n");
+        let payments = parse_payments(&csv).unwrap();
+
+        let occurrences: Vec<u32> = payments.iter().map(|payment| payment.occurrence).collect();
+        assert_eq!(occurrences, [0, 0, 1, 0]);
+        assert_ne!(payments[0].key(), payments[2].key());
+    }
+
+    #[test]
+    fn parse_payments_rejects_invalid_rows() {
+        let only_header_once = format!(
+            "recipient,faucet,amount\n{}\nrecipient,faucet,amount",
+            row(wallet(1), 1)
+        );
+        let not_a_faucet = format!("{},{},5", wallet(1).to_hex(), wallet(2).to_hex());
+        for (csv, line) in [
+            (
+                format!("{}\n{},5", row(wallet(1), 1), wallet(2).to_hex()),
+                2,
+            ),
+            (row(wallet(1), 0), 1),
+            (
+                format!("{},{},ten", wallet(1).to_hex(), faucet().to_hex()),
+                1,
+            ),
+            (not_a_faucet, 1),
+            (only_header_once, 3),
+        ] {
+            match parse_payments(&csv) {
+                Err(DisperseError::InvalidRow { line: invalid, .. }) => assert_eq!(invalid, line),
+                other => panic!("expected invalid row {line} in {csv:?}, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn journal_drops_truncated_last_record() {
+        let path = journal_path("truncated");
+        let payments =
+            parse_payments(&[row(wallet(1), 10), row(wallet(2), 20)].join("\n")).unwrap();
+
+        let mut journal = Journal::read(&path, wallet(7)).unwrap();
+        let first = pending_batch(&journal, &payments[..1]);
+        journal.append(first.record(wallet(7))).unwrap();
+        drop(journal);
+
+        // A crash while appending the second record leaves half a line behind
+        let complete = fs::read_to_string(&path).unwrap();
+        let second = serde_json::to_string(
+            &pending_batch(&Journal::read(&path, wallet(7)).unwrap(), &payments[1..])
+                .record(wallet(7)),
+        )
+        .unwrap();
+        fs::write(&path, format!("{complete}{}", &second[..second.len() / 2])).unwrap();
+
+        let mut journal = Journal::read(&path, wallet(7)).unwrap();
+        assert_eq!(journal.pending().len(), 1);
+        assert_eq!(journal.pending()[0].payments, [payments[0].key()]);
+
+        // The next append replaces the torn record
+        journal
+            .append(first.resolution(BatchStatus::Expired))
+            .unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with(&complete));
+        assert_eq!(content.lines().count(), 2);
+        assert!(Journal::read(&path, wallet(7))
+            .unwrap()
+            .pending()
+            .is_empty());
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn unpaid_payments_skips_pending_and_committed_batches() {
+        let path = journal_path("unpaid");
+        let csv = [
+            row(wallet(1), 10),
+            row(wallet(2), 20),
+            row(wallet(3), 30),
+            row(wallet(4), 40),
+        ]
+        .join("\n");
+        let payments = parse_payments(&csv).unwrap();
+
+        let mut journal = Journal::read(&path, wallet(7)).unwrap();
+        let pending = pending_batch(&journal, &payments[0..1]);
+        journal.append(pending.record(wallet(7))).unwrap();
+        let expired = pending_batch(&journal, &payments[1..2]);
+        journal.append(expired.record(wallet(7))).unwrap();
+        journal
+            .append(expired.resolution(BatchStatus::Expired))
+            .unwrap();
+        let committed = pending_batch(&journal, &payments[2..3]);
+        journal.append(committed.record(wallet(7))).unwrap();
+        journal
+            .append(committed.resolution(BatchStatus::Committed(12)))
+            .unwrap();
+        drop(journal);
+
+        let unpaid = unpaid_payments(wallet(7), &payments, &path).unwrap();
+        assert_eq!(unpaid, [payments[1].clone(), payments[3].clone()]);
+
+        // Reordering the CSV file does not change which payments were made
+        let mut reordered = payments.clone();
+        reordered.reverse();
+        let reordered = parse_payments(
+            &reordered
+                .iter()
+                .map(|payment| row(payment.recipient, payment.amount))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        )
+        .unwrap();
+        let unpaid = unpaid_payments(wallet(7), &reordered, &path).unwrap();
+        let recipients: Vec<AccountId> = unpaid.iter().map(|payment| payment.recipient).collect();
+        assert_eq!(recipients, [wallet(4), wallet(2)]);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn journal_of_another_sender_is_refused() {
+        let path = journal_path("sender");
+        let payments = parse_payments(&row(wallet(1), 10)).unwrap();
+
+        let mut journal = Journal::read(&path, wallet(7)).unwrap();
+        let batch = pending_batch(&journal, &payments);
+        journal.append(batch.record(wallet(7))).unwrap();
+        drop(journal);
+
+        match unpaid_payments(wallet(8), &payments, &path) {
+            Err(DisperseError::SenderMismatch {
+                journal_sender,
+                sender,
+                ..
+            }) => {
+                assert_eq!(journal_sender, wallet(7));
+                assert_eq!(sender, wallet(8));
+            }
+            other => panic!("expected a sender mismatch, got {other:?}"),
+        }
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,382 @@
+use miden_client::{
+    accounts::{Account, AccountId},
+    assets::Asset,
+    auth::TransactionAuthenticator,
+    crypto::{Digest, RpoRandomCoin},
+    notes::NoteId,
+    transactions::{
+        ExecutedTransaction, TransactionExecutorError, TransactionId, TransactionRequest,
+    },
+    Client, ClientError, Felt, Word,
+};
+use miden_objects::{
+    accounts::StorageMapDelta,
+    transaction::{TransactionInputs, TransactionMeasurements},
+    AccountError,
+};
+use miden_tx::{DataStore, DataStoreError, TransactionExecutor};
+
+use async_trait::async_trait;
+use std::{env, fmt, sync::Arc};
+
+/// Command line flag selecting dry runs.
+const DRY_RUN_FLAG: &str = "--dry-run";
+
+/// Environment variable selecting dry runs when set to `1` or `true`.
+pub const DRY_RUN_ENV_VAR: &str = "MIDEN_DRY_RUN";
+
+/// Errors that can occur while dry running a transaction.
+#[derive(Debug)]
+pub enum DryRunError {
+    /// The transaction could not be executed.
+    Client(Box<ClientError>),
+    /// Executing the transaction again to measure its cycles failed.
+    Replay(TransactionExecutorError),
+    /// The account delta of the transaction could not be applied to the initial account.
+    Account(AccountError),
+}
+
+impl fmt::Display for DryRunError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DryRunError::Client(err) => write!(f, "failed to execute transaction: {err}"),
+            DryRunError::Replay(err) => write!(f, "failed to measure transaction cycles: {err}"),
+            DryRunError::Account(err) => write!(f, "failed to apply account delta: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for DryRunError {}
+
+impl From<DryRunError> for String {
+    fn from(err: DryRunError) -> String {
+        err.to_string()
+    }
+}
+
+impl From<ClientError> for DryRunError {
+    fn from(err: ClientError) -> Self {
+        Self::Client(Box::new(err))
+    }
+}
+
+impl From<TransactionExecutorError> for DryRunError {
+    fn from(err: TransactionExecutorError) -> Self {
+        Self::Replay(err)
+    }
+}
+
+impl From<AccountError> for DryRunError {
+    fn from(err: AccountError) -> Self {
+        Self::Account(err)
+    }
+}
+
+/// Returns whether transactions should be dry run instead of submitted: if the `--dry-run`
+/// command line flag is given or `MIDEN_DRY_RUN` is set to `1` or `true`.
+pub fn dry_run_requested() -> bool {
+    env::args().skip(1).any(|arg| arg == DRY_RUN_FLAG)
+        || env::var(DRY_RUN_ENV_VAR).is_ok_and(|value| value == "1" || value == "true")
+}
+
+/// A storage slot changed by a transaction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SlotDiff {
+    pub index: u8,
+    /// Value of the slot, the root of the map for map slots.
+    pub before: Word,
+    pub after: Word,
+    /// Changed entries of a map slot.
+    pub map_entries: Vec<MapEntryDiff>,
+}
+
+/// An entry of a storage map changed by a transaction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MapEntryDiff {
+    pub key: Digest,
+    pub before: Word,
+    pub after: Word,
+}
+
+/// A note consumed or created by a transaction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NoteSummary {
+    pub id: NoteId,
+    /// Assets of the note, empty if only the note header is known.
+    pub assets: Vec<Asset>,
+}
+
+/// The changes a transaction would make, built from its execution without submitting it.
+#[derive(Clone, Debug)]
+pub struct DryRunReport {
+    pub tx_id: TransactionId,
+    pub account_id: AccountId,
+    pub storage: Vec<SlotDiff>,
+    /// Assets added to the account vault.
+    pub added_assets: Vec<Asset>,
+    /// Assets removed from the account vault.
+    pub removed_assets: Vec<Asset>,
+    pub nonce_before: Felt,
+    pub nonce_after: Felt,
+    pub output_notes: Vec<NoteSummary>,
+    pub input_notes: Vec<NoteSummary>,
+    /// VM cycles spent in each stage of the transaction.
+    pub cycles: TransactionMeasurements,
+}
+
+impl DryRunReport {
+    /// Builds the report of an executed transaction.
+    ///
+    /// Cycles are only measured if the transaction was executed with tracing enabled, see
+    /// [`dry_run`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the account delta of the transaction does not apply to its initial
+    /// account.
+    pub fn new(executed: &ExecutedTransaction) -> Result<Self, DryRunError> {
+        let before = executed.initial_account();
+        let delta = executed.account_delta();
+        let mut after = before.clone();
+        after.apply_delta(delta)?;
+
+        let mut indices: Vec<u8> = delta
+            .storage()
+            .values()
+            .keys()
+            .chain(delta.storage().maps().keys())
+            .copied()
+            .collect();
+        indices.sort_unstable();
+        indices.dedup();
+        let storage = indices
+            .into_iter()
+            .map(|index| slot_diff(before, &after, delta.storage().maps().get(&index), index))
+            .collect::<Result<_, AccountError>>()?;
+
+        Ok(Self {
+            tx_id: executed.id(),
+            account_id: executed.account_id(),
+            storage,
+            added_assets: delta.vault().added_assets().collect(),
+            removed_assets: delta.vault().removed_assets().collect(),
+            nonce_before: before.nonce(),
+            nonce_after: after.nonce(),
+            output_notes: executed
+                .output_notes()
+                .iter()
+                .map(|note| NoteSummary {
+                    id: note.id(),
+                    assets: note
+                        .assets()
+                        .map(|assets| assets.iter().copied().collect())
+                        .unwrap_or_default(),
+                })
+                .collect(),
+            input_notes: executed
+                .input_notes()
+                .iter()
+                .map(|note| NoteSummary {
+                    id: note.id(),
+                    assets: note.note().assets().iter().copied().collect(),
+                })
+                .collect(),
+            cycles: executed.measurements().clone(),
+        })
+    }
+}
+
+fn slot_diff(
+    before: &Account,
+    after: &Account,
+    map_delta: Option<&StorageMapDelta>,
+    index: u8,
+) -> Result<SlotDiff, AccountError> {
+    let map_entries = match map_delta {
+        Some(map_delta) => map_delta
+            .leaves()
+            .iter()
+            .map(|(key, value)| {
+                Ok(MapEntryDiff {
+                    key: *key,
+                    before: before.storage().get_map_item(index, (*key).into())?,
+                    after: *value,
+                })
+            })
+            .collect::<Result<_, AccountError>>()?,
+        None => Vec::new(),
+    };
+
+    Ok(SlotDiff {
+        index,
+        before: before.storage().get_item(index)?.into(),
+        after: after.storage().get_item(index)?.into(),
+        map_entries,
+    })
+}
+
+impl fmt::Display for DryRunReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "dry run of transaction {} against account {}",
+            self.tx_id,
+            self.account_id.to_hex()
+        )?;
+
+        writeln!(f, "storage:")?;
+        if self.storage.is_empty() {
+            writeln!(f, "  unchanged")?;
+        }
+        for slot in &self.storage {
+            writeln!(
+                f,
+                "  slot {}: {} -> {}",
+                slot.index,
+                WordDisplay(&slot.before),
+                WordDisplay(&slot.after)
+            )?;
+            for entry in &slot.map_entries {
+                writeln!(
+                    f,
+                    "    key {}: {} -> {}",
+                    entry.key,
+                    WordDisplay(&entry.before),
+                    WordDisplay(&entry.after)
+                )?;
+            }
+        }
+
+        writeln!(f, "vault:")?;
+        if self.added_assets.is_empty() && self.removed_assets.is_empty() {
+            writeln!(f, "  unchanged")?;
+        }
+        for asset in &self.added_assets {
+            writeln!(f, "  + {}", AssetDisplay(asset))?;
+        }
+        for asset in &self.removed_assets {
+            writeln!(f, "  - {}", AssetDisplay(asset))?;
+        }
+
+        writeln!(f, "nonce: {} -> {}", self.nonce_before, self.nonce_after)?;
+
+        for (label, notes) in [
+            ("output notes", &self.output_notes),
+            ("input notes", &self.input_notes),
+        ] {
+            writeln!(f, "{label}: {}", notes.len())?;
+            for note in notes {
+                let assets: Vec<_> = note
+                    .assets
+                    .iter()
+                    .map(|asset| AssetDisplay(asset).to_string())
+                    .collect();
+                writeln!(f, "  {} [{}]", note.id, assets.join(", "))?;
+            }
+        }
+
+        write!(
+            f,
+            "cycles: {} (prologue {}, notes {}, script {}, epilogue {})",
+            self.cycles.total_cycles(),
+            self.cycles.prologue,
+            self.cycles.notes_processing,
+            self.cycles.tx_script_processing,
+            self.cycles.epilogue
+        )
+    }
+}
+
+struct WordDisplay<'a>(&'a Word);
+
+impl fmt::Display for WordDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [a, b, c, d] = self.0.map(|felt| felt.as_int());
+        write!(f, "[{a}, {b}, {c}, {d}]")
+    }
+}
+
+struct AssetDisplay<'a>(&'a Asset);
+
+impl fmt::Display for AssetDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Asset::Fungible(asset) => write!(
+                f,
+                "{} of faucet {}",
+                asset.amount(),
+                asset.faucet_id().to_hex()
+            ),
+            Asset::NonFungible(asset) => write!(
+                f,
+                "non-fungible asset of faucet {}",
+                asset.faucet_id().to_hex()
+            ),
+        }
+    }
+}
+
+/// Serves the inputs of an already executed transaction, to execute it again.
+///
+/// These are the inputs the data store of the client served for the first execution, which
+/// miden-client does not expose.
+struct ReplayDataStore(TransactionInputs);
+
+#[async_trait(?Send)]
+impl DataStore for ReplayDataStore {
+    async fn get_transaction_inputs(
+        &self,
+        _account_id: AccountId,
+        _block_num: u32,
+        _notes: &[NoteId],
+    ) -> Result<TransactionInputs, DataStoreError> {
+        Ok(self.0.clone())
+    }
+}
+
+/// Executes `executed` again with tracing enabled, so the cycles of each stage are measured.
+///
+/// The client executes transactions without tracing. `authenticator` signs the replay again,
+/// so it must hold the keys of the client that executed `executed`.
+async fn replay_with_tracing(
+    executed: &ExecutedTransaction,
+    authenticator: Arc<dyn TransactionAuthenticator>,
+) -> Result<ExecutedTransaction, DryRunError> {
+    let note_ids: Vec<NoteId> = executed
+        .input_notes()
+        .iter()
+        .map(|note| note.id())
+        .collect();
+
+    let data_store = ReplayDataStore(executed.tx_inputs().clone());
+    let executor =
+        TransactionExecutor::new(Arc::new(data_store), Some(authenticator)).with_tracing();
+    Ok(executor
+        .execute_transaction(
+            executed.account_id(),
+            executed.block_header().block_num(),
+            &note_ids,
+            executed.tx_args().clone(),
+        )
+        .await?)
+}
+
+/// Executes `request` against `account_id` without proving or submitting the transaction, and
+/// returns the changes it would make.
+///
+/// The client store is left untouched, so the same request can be submitted afterwards.
+/// `authenticator` is the one `client` was built with, see
+/// [`ClientBuilder::build_with_authenticator`].
+///
+/// [`ClientBuilder::build_with_authenticator`]: super::ClientBuilder::build_with_authenticator
+pub async fn dry_run(
+    client: &mut Client<RpoRandomCoin>,
+    authenticator: &Arc<dyn TransactionAuthenticator>,
+    account_id: AccountId,
+    request: TransactionRequest,
+) -> Result<DryRunReport, DryRunError> {
+    let tx_result = client.new_transaction(account_id, request).await?;
+    let executed =
+        replay_with_tracing(tx_result.executed_transaction(), authenticator.clone()).await?;
+    DryRunReport::new(&executed)
+}
@@ -0,0 +1,368 @@
+use miden_client::{
+    auth::TransactionAuthenticator,
+    crypto::Digest,
+    utils::{Deserializable, Serializable},
+    Felt, Word,
+};
+use miden_objects::accounts::{AccountDelta, AuthSecretKey};
+use miden_tx::{auth::signatures::get_falcon_signature, utils::sync::RwLock, AuthenticationError};
+
+use argon2::Argon2;
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use rand::{rngs::OsRng, Rng, RngCore};
+use std::{
+    fmt, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Magic bytes at the start of every key file.
+const KEY_FILE_MAGIC: &[u8; 4] = b"MDNK";
+
+/// Version of the key file format.
+const KEY_FILE_VERSION: u8 = 1;
+
+/// Extension of the key files inside the keystore directory.
+const KEY_FILE_EXTENSION: &str = "key";
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const HEADER_LEN: usize = KEY_FILE_MAGIC.len() + 1 + SALT_LEN + NONCE_LEN;
+
+/// Errors that can occur while reading or writing keys in a [`FilesystemKeyStore`].
+#[derive(Debug)]
+pub enum KeyStoreError {
+    /// Reading or writing a key file failed.
+    Io(PathBuf, io::Error),
+    /// No key file exists for the given public key.
+    KeyNotFound(Digest),
+    /// The file is not a key file or was written by an unsupported version.
+    InvalidKeyFile(PathBuf, String),
+    /// The key could not be decrypted, either because the password is wrong or the file was
+    /// tampered with.
+    DecryptionFailed(PathBuf),
+    /// Deriving the encryption key from the password failed.
+    KeyDerivation(String),
+}
+
+impl fmt::Display for KeyStoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyStoreError::Io(path, err) => write!(f, "failed to access {}: {err}", path.display()),
+            KeyStoreError::KeyNotFound(pub_key) => {
+                write!(f, "no key stored for public key {pub_key}")
+            }
+            KeyStoreError::InvalidKeyFile(path, err) => {
+                write!(f, "{} is not a valid key file: {err}", path.display())
+            }
+            KeyStoreError::DecryptionFailed(path) => write!(
+                f,
+                "failed to decrypt {}: wrong password or corrupted file",
+                path.display()
+            ),
+            KeyStoreError::KeyDerivation(err) => {
+                write!(f, "failed to derive encryption key: {err}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for KeyStoreError {}
+
+/// Stores account secret keys as password-encrypted files, one file per key named after the
+/// hex encoded public key (e.g. `0x1234...abcd.key`).
+///
+/// Keys are encrypted with ChaCha20-Poly1305 using a key derived from the password with
+/// Argon2id. The public key is bound to the ciphertext so key files cannot be swapped.
+#[derive(Clone, Debug)]
+pub struct FilesystemKeyStore {
+    dir: PathBuf,
+}
+
+impl FilesystemKeyStore {
+    /// Opens the keystore at `dir`, creating the directory if it does not exist.
+    pub fn new<P: AsRef<Path>>(dir: P) -> Result<Self, KeyStoreError> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir).map_err(|err| KeyStoreError::Io(dir.clone(), err))?;
+
+        Ok(Self { dir })
+    }
+
+    /// Returns the directory backing this keystore.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Encrypts `key` with `password` and writes it to the keystore. Returns the public key the
+    /// file is stored under. An existing file for the same public key is replaced.
+    ///
+    /// The file is written to a temporary file readable only by the owner and renamed into
+    /// place, so a crash never leaves a partially written key behind.
+    pub fn add_key(&self, key: &AuthSecretKey, password: &str) -> Result<Word, KeyStoreError> {
+        let pub_key = public_key_of(key);
+        let path = self.key_path(pub_key);
+        let contents = encrypt_key(key, pub_key, password)?;
+        write_key_file(&path, &contents).map_err(|err| KeyStoreError::Io(path, err))?;
+
+        Ok(pub_key)
+    }
+
+    /// Reads and decrypts the secret key stored for `pub_key`.
+    pub fn get_key(&self, pub_key: Word, password: &str) -> Result<AuthSecretKey, KeyStoreError> {
+        let path = self.key_path(pub_key);
+        if !path.is_file() {
+            return Err(KeyStoreError::KeyNotFound(pub_key.into()));
+        }
+
+        read_key_file(&path, pub_key, password)
+    }
+
+    /// Returns `true` if a key file exists for `pub_key`.
+    pub fn contains_key(&self, pub_key: Word) -> bool {
+        self.key_path(pub_key).is_file()
+    }
+
+    /// Returns the public keys of all keys in the keystore.
+    pub fn list_keys(&self) -> Result<Vec<Word>, KeyStoreError> {
+        let entries =
+            fs::read_dir(&self.dir).map_err(|err| KeyStoreError::Io(self.dir.clone(), err))?;
+
+        let mut keys = Vec::new();
+        for entry in entries {
+            let path = entry
+                .map_err(|err| KeyStoreError::Io(self.dir.clone(), err))?
+                .path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some(KEY_FILE_EXTENSION) {
+                continue;
+            }
+            let digest = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| Digest::try_from(stem).ok());
+            if let Some(digest) = digest {
+                keys.push(digest.into());
+            }
+        }
+
+        Ok(keys)
+    }
+
+    /// Copies the encrypted key file for `pub_key` to `destination`, e.g. to hand it to a
+    /// teammate. The exported file stays encrypted with the keystore password.
+    pub fn export_key<P: AsRef<Path>>(
+        &self,
+        pub_key: Word,
+        destination: P,
+    ) -> Result<(), KeyStoreError> {
+        let path = self.key_path(pub_key);
+        if !path.is_file() {
+            return Err(KeyStoreError::KeyNotFound(pub_key.into()));
+        }
+        let destination = destination.as_ref();
+        fs::copy(&path, destination)
+            .map(drop)
+            .map_err(|err| KeyStoreError::Io(destination.to_path_buf(), err))
+    }
+
+    /// Imports a key file exported from another keystore. The file is decrypted with
+    /// `file_password` and stored re-encrypted with `password`. Returns the imported public key.
+    pub fn import_key<P: AsRef<Path>>(
+        &self,
+        source: P,
+        file_password: &str,
+        password: &str,
+    ) -> Result<Word, KeyStoreError> {
+        let source = source.as_ref();
+        let pub_key = source
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| Digest::try_from(stem).ok())
+            .ok_or_else(|| {
+                KeyStoreError::InvalidKeyFile(
+                    source.to_path_buf(),
+                    "file name is not a hex encoded public key".to_string(),
+                )
+            })?;
+
+        let key = read_key_file(source, pub_key.into(), file_password)?;
+        self.add_key(&key, password)
+    }
+
+    fn key_path(&self, pub_key: Word) -> PathBuf {
+        self.dir
+            .join(Digest::from(pub_key).to_hex())
+            .with_extension(KEY_FILE_EXTENSION)
+    }
+}
+
+/// A [`TransactionAuthenticator`] signing with keys from a [`FilesystemKeyStore`].
+///
+/// Keys are decrypted on demand, so keys added to the keystore after the client was created
+/// can be used right away. Public keys not found in the keystore are delegated to the
+/// optional fallback authenticator (e.g. the `StoreAuthenticator` for accounts created
+/// through `Client::new_account`).
+pub struct KeyStoreAuthenticator<R> {
+    keystore: FilesystemKeyStore,
+    password: String,
+    fallback: Option<Arc<dyn TransactionAuthenticator + Send + Sync>>,
+    rng: Arc<RwLock<R>>,
+}
+
+impl<R: Rng> KeyStoreAuthenticator<R> {
+    pub fn new_with_rng(keystore: FilesystemKeyStore, password: String, rng: R) -> Self {
+        Self {
+            keystore,
+            password,
+            fallback: None,
+            rng: Arc::new(RwLock::new(rng)),
+        }
+    }
+
+    /// Delegates signature requests for keys missing from the keystore to `fallback`.
+    pub fn with_fallback(
+        mut self,
+        fallback: Arc<dyn TransactionAuthenticator + Send + Sync>,
+    ) -> Self {
+        self.fallback = Some(fallback);
+        self
+    }
+}
+
+impl<R: Rng> TransactionAuthenticator for KeyStoreAuthenticator<R> {
+    fn get_signature(
+        &self,
+        pub_key: Word,
+        message: Word,
+        account_delta: &AccountDelta,
+    ) -> Result<Vec<Felt>, AuthenticationError> {
+        if !self.keystore.contains_key(pub_key) {
+            return match &self.fallback {
+                Some(fallback) => fallback.get_signature(pub_key, message, account_delta),
+                None => Err(AuthenticationError::UnknownKey(format!(
+                    "{}",
+                    Digest::from(pub_key)
+                ))),
+            };
+        }
+
+        let secret_key = self
+            .keystore
+            .get_key(pub_key, &self.password)
+            .map_err(|err| AuthenticationError::InternalError(err.to_string()))?;
+
+        let mut rng = self.rng.write();
+        let AuthSecretKey::RpoFalcon512(k) = secret_key;
+        get_falcon_signature(&k, message, &mut *rng)
+    }
+}
+
+fn public_key_of(key: &AuthSecretKey) -> Word {
+    let AuthSecretKey::RpoFalcon512(k) = key;
+    k.public_key().into()
+}
+
+fn derive_cipher(password: &str, salt: &[u8]) -> Result<ChaCha20Poly1305, KeyStoreError> {
+    let mut key = [0_u8; 32];
+    Argon2::default()
+        .hash_password_into(password.as_bytes(), salt, &mut key)
+        .map_err(|err| KeyStoreError::KeyDerivation(err.to_string()))?;
+
+    Ok(ChaCha20Poly1305::new(Key::from_slice(&key)))
+}
+
+fn encrypt_key(
+    key: &AuthSecretKey,
+    pub_key: Word,
+    password: &str,
+) -> Result<Vec<u8>, KeyStoreError> {
+    let mut salt = [0_u8; SALT_LEN];
+    let mut nonce = [0_u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut salt);
+    OsRng.fill_bytes(&mut nonce);
+
+    let aad = Digest::from(pub_key).as_bytes();
+    let plaintext = key.to_bytes();
+    let ciphertext = derive_cipher(password, &salt)?
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: &plaintext,
+                aad: &aad,
+            },
+        )
+        .expect("encrypting a secret key in memory should not fail");
+
+    let mut contents = Vec::with_capacity(HEADER_LEN + ciphertext.len());
+    contents.extend_from_slice(KEY_FILE_MAGIC);
+    contents.push(KEY_FILE_VERSION);
+    contents.extend_from_slice(&salt);
+    contents.extend_from_slice(&nonce);
+    contents.extend_from_slice(&ciphertext);
+
+    Ok(contents)
+}
+
+/// Writes `contents` to a temporary file with owner-only permissions, flushes it to disk and
+/// renames it to `path`.
+fn write_key_file(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let tmp_path = path.with_extension(format!("tmp-{}", std::process::id()));
+    let written = (|| {
+        // A leftover of a crashed write may have other permissions, start from a new file
+        let _ = fs::remove_file(&tmp_path);
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+        let mut file = options.open(&tmp_path)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, path)
+    })();
+    if written.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    written
+}
+
+fn read_key_file(
+    path: &Path,
+    pub_key: Word,
+    password: &str,
+) -> Result<AuthSecretKey, KeyStoreError> {
+    let contents = fs::read(path).map_err(|err| KeyStoreError::Io(path.to_path_buf(), err))?;
+    let invalid = |err: &str| KeyStoreError::InvalidKeyFile(path.to_path_buf(), err.to_string());
+
+    if contents.len() <= HEADER_LEN || !contents.starts_with(KEY_FILE_MAGIC) {
+        return Err(invalid("missing key file header"));
+    }
+    let version = contents[KEY_FILE_MAGIC.len()];
+    if version != KEY_FILE_VERSION {
+        return Err(invalid(&format!("unsupported version {version}")));
+    }
+    let (salt, rest) = contents[KEY_FILE_MAGIC.len() + 1..].split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+
+    let aad = Digest::from(pub_key).as_bytes();
+    let plaintext = derive_cipher(password, salt)?
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: &aad,
+            },
+        )
+        .map_err(|_| KeyStoreError::DecryptionFailed(path.to_path_buf()))?;
+
+    let key =
+        AuthSecretKey::read_from_bytes(&plaintext).map_err(|err| invalid(&err.to_string()))?;
+    if public_key_of(&key) != pub_key {
+        return Err(invalid("public key does not match the file name"));
+    }
+
+    Ok(key)
+}
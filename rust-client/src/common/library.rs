@@ -0,0 +1,112 @@
+use miden_assembly::ast::{Module, ModuleKind};
+use miden_client::transactions::{TransactionKernel, TransactionScript};
+use miden_objects::{
+    assembly::{Assembler, Library, LibraryPath},
+    notes::NoteScript,
+    vm::Program,
+    Felt, Word,
+};
+
+use std::fmt;
+
+/// Namespace of the libraries built from the account contracts under `masm/accounts`.
+pub const EXTERNAL_CONTRACT_NAMESPACE: &str = "external_contract";
+
+/// Whether libraries and scripts are assembled in debug mode, part of the [`MastCache`] key.
+///
+/// [`MastCache`]: super::MastCache
+pub(super) const ASSEMBLER_DEBUG_MODE: bool = true;
+
+/// Errors that can occur while building a contract library or linking it into a script.
+#[derive(Debug)]
+pub enum LibraryError {
+    /// The library path is not a valid MASM module path, e.g. `external_contract::counter`.
+    InvalidPath(String),
+    /// The library source could not be parsed or assembled.
+    Assembly(String),
+    /// The script linking the libraries could not be compiled.
+    Script(String),
+}
+
+impl fmt::Display for LibraryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LibraryError::InvalidPath(path) => write!(f, "invalid library path `{path}`"),
+            LibraryError::Assembly(err) => write!(f, "failed to assemble library: {err}"),
+            LibraryError::Script(err) => write!(f, "failed to compile script: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for LibraryError {}
+
+impl From<LibraryError> for String {
+    fn from(err: LibraryError) -> String {
+        err.to_string()
+    }
+}
+
+/// Compiles `source` into a library whose single module is named `library_path`, so scripts
+/// linked against it can `use.<library_path>` and call its exports by name, e.g.
+/// `call.counter_contract::increment_count` for `external_contract::counter_contract`.
+///
+/// The library is assembled with the same settings as account components, so its procedure
+/// roots match the ones of accounts built from the same source.
+pub fn create_library(library_path: &str, source: &str) -> Result<Library, LibraryError> {
+    let assembler = assembler();
+    let path = LibraryPath::new(library_path)
+        .map_err(|_| LibraryError::InvalidPath(library_path.to_string()))?;
+    let module = Module::parser(ModuleKind::Library)
+        .parse_str(path, source, &assembler.source_manager())
+        .map_err(|report| LibraryError::Assembly(report.to_string()))?;
+
+    assembler
+        .assemble_library([module])
+        .map_err(|report| LibraryError::Assembly(report.to_string()))
+}
+
+/// Like `Client::compile_tx_script`, but links `libraries` so the script can `use` them.
+pub fn compile_tx_script_with_libraries<T>(
+    inputs: T,
+    program: &str,
+    libraries: &[Library],
+) -> Result<TransactionScript, LibraryError>
+where
+    T: IntoIterator<Item = (Word, Vec<Felt>)>,
+{
+    Ok(TransactionScript::new(
+        assemble_program(program, libraries)?,
+        inputs,
+    ))
+}
+
+/// Compiles a note script that can `use` the given `libraries`.
+pub fn compile_note_script_with_libraries(
+    program: &str,
+    libraries: &[Library],
+) -> Result<NoteScript, LibraryError> {
+    Ok(NoteScript::new(assemble_program(program, libraries)?))
+}
+
+/// Assembles `program` with `libraries` linked.
+pub(super) fn assemble_program(
+    program: &str,
+    libraries: &[Library],
+) -> Result<Program, LibraryError> {
+    linking_assembler(libraries)?
+        .assemble_program(program)
+        .map_err(|report| LibraryError::Script(report.to_string()))
+}
+
+fn linking_assembler(libraries: &[Library]) -> Result<Assembler, LibraryError> {
+    libraries
+        .iter()
+        .try_fold(assembler(), |assembler, library| {
+            assembler.with_library(library)
+        })
+        .map_err(|report| LibraryError::Assembly(report.to_string()))
+}
+
+fn assembler() -> Assembler {
+    TransactionKernel::assembler().with_debug_mode(ASSEMBLER_DEBUG_MODE)
+}
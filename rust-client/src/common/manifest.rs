@@ -0,0 +1,599 @@
+use miden_client::{
+    accounts::{Account, AccountStorageMode, AccountType},
+    Felt, StarkField, Word,
+};
+use miden_objects::{
+    accounts::{AccountComponent, AuthSecretKey, StorageMap, StorageSlot},
+    assembly::Library,
+    crypto::hash::rpo::RpoDigest,
+    AccountError,
+};
+
+use figment::{
+    providers::{Format, Toml},
+    Figment,
+};
+use serde::Deserialize;
+use std::{
+    collections::BTreeSet,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use super::{
+    create_account, create_library, AccountAuth, AccountOptions, KeyGeneration, LibraryError,
+    MastCache, ProcedureRoots, SlotKind, SlotSchema, SlotType, StorageSchema,
+    EXTERNAL_CONTRACT_NAMESPACE,
+};
+
+/// Errors that can occur while loading an [`AccountManifest`] or building its account.
+#[derive(Debug)]
+pub enum ManifestError {
+    /// The manifest is not valid TOML or does not match the manifest format.
+    Malformed(PathBuf, Box<figment::Error>),
+    /// The manifest or the MASM source it references could not be read.
+    Io(PathBuf, io::Error),
+    /// An account type name is not one of the supported names.
+    UnknownAccountType(String),
+    /// The storage mode is neither `public` nor `private`.
+    UnknownStorageMode(String),
+    /// The authentication scheme is not supported.
+    UnsupportedAuthScheme(String),
+    /// A storage value is not a valid field element.
+    InvalidFelt(String, u64),
+    /// Two storage slots share the same name.
+    DuplicateSlot(String),
+    /// A slot value or key type is not one of the supported types.
+    UnknownSlotType(String),
+    /// A storage slot declaration is inconsistent, e.g. an initial value does not match the
+    /// slot type.
+    InvalidSlot { slot: String, reason: String },
+    /// Compiling the component or building the account failed.
+    Account(AccountError),
+    /// Building the contract library failed.
+    Library(LibraryError),
+}
+
+impl fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ManifestError::Malformed(path, err) => {
+                write!(f, "failed to parse manifest {}: {err}", path.display())
+            }
+            ManifestError::Io(path, err) => write!(f, "failed to read {}: {err}", path.display()),
+            ManifestError::UnknownAccountType(name) => write!(
+                f,
+                "unknown account type `{name}`, expected one of: all, {}",
+                ACCOUNT_TYPE_NAMES.map(|(name, _)| name).join(", ")
+            ),
+            ManifestError::UnknownStorageMode(name) => {
+                write!(
+                    f,
+                    "unknown storage mode `{name}`, expected public or private"
+                )
+            }
+            ManifestError::UnsupportedAuthScheme(name) => {
+                write!(
+                    f,
+                    "unsupported auth scheme `{name}`, expected rpo-falcon512"
+                )
+            }
+            ManifestError::InvalidFelt(slot, value) => {
+                write!(
+                    f,
+                    "value {value} of storage slot `{slot}` is not a field element"
+                )
+            }
+            ManifestError::DuplicateSlot(name) => {
+                write!(f, "storage slot `{name}` is declared twice")
+            }
+            ManifestError::UnknownSlotType(name) => write!(
+                f,
+                "unknown slot type `{name}`, expected one of: {}",
+                SlotType::NAMES.map(|(name, _)| name).join(", ")
+            ),
+            ManifestError::InvalidSlot { slot, reason } => {
+                write!(f, "invalid storage slot `{slot}`: {reason}")
+            }
+            ManifestError::Account(err) => write!(f, "failed to build account: {err}"),
+            ManifestError::Library(err) => write!(f, "failed to build contract library: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ManifestError {}
+
+impl From<ManifestError> for String {
+    fn from(err: ManifestError) -> String {
+        err.to_string()
+    }
+}
+
+impl From<AccountError> for ManifestError {
+    fn from(err: AccountError) -> Self {
+        Self::Account(err)
+    }
+}
+
+impl From<LibraryError> for ManifestError {
+    fn from(err: LibraryError) -> Self {
+        Self::Library(err)
+    }
+}
+
+/// Account type names accepted in `supported_types` and `account_type`.
+const ACCOUNT_TYPE_NAMES: [(&str, AccountType); 4] = [
+    (
+        "regular-updatable",
+        AccountType::RegularAccountUpdatableCode,
+    ),
+    (
+        "regular-immutable",
+        AccountType::RegularAccountImmutableCode,
+    ),
+    ("fungible-faucet", AccountType::FungibleFaucet),
+    ("non-fungible-faucet", AccountType::NonFungibleFaucet),
+];
+
+/// The only authentication scheme currently supported by manifests.
+const RPO_FALCON_512_AUTH: &str = "rpo-falcon512";
+
+/// Describes a deployable account contract: its MASM source, named storage slots and how the
+/// account is created. Example (`masm/accounts/counter.toml`):
+///
+/// ```toml
+/// name = "counter"
+/// source = "counter.masm"
+/// library = "external_contract::counter_contract"
+/// supported_types = ["all"]
+/// account_type = "regular-updatable"
+/// storage_mode = "public"
+/// auth = "rpo-falcon512"
+///
+/// [[storage]]
+/// name = "count"
+/// type = "value"
+/// value_type = "u64"
+/// value = 0
+///
+/// [[procedures]]
+/// name = "increment_count"
+/// description = "Increments the count by one."
+/// ```
+///
+/// A value slot holds a single number or a word of four numbers, a map slot holds a list of
+/// `{ key = [..], value = [..] }` entries. `value_type` (and `key_type` for maps) declares how
+/// the slot is read, one of `word` (the default), `felt`, `u64` or `account-id`.
+///
+/// Each `[[procedures]]` entry is the signature of an exported procedure, from which
+/// `build.rs` generates a Rust binding, e.g. `accounts::counter::CounterContract`. See
+/// [`ProcedureManifest`].
+#[derive(Debug, Deserialize)]
+pub struct AccountManifest {
+    /// Name of the contract.
+    pub name: String,
+    /// Path of the MASM source, relative to the manifest.
+    pub source: PathBuf,
+    /// Module path scripts `use` to call the contract, defaults to
+    /// `external_contract::<name>_contract`.
+    pub library: Option<String>,
+    /// Account types the component supports, or `["all"]`.
+    #[serde(default = "default_supported_types")]
+    pub supported_types: Vec<String>,
+    /// Type of the account created from the manifest.
+    #[serde(default = "default_account_type")]
+    pub account_type: String,
+    /// `public` or `private`.
+    #[serde(default = "default_storage_mode")]
+    pub storage_mode: String,
+    /// Authentication scheme of the account.
+    #[serde(default = "default_auth")]
+    pub auth: String,
+    /// Storage slots of the component, in slot index order.
+    #[serde(default)]
+    pub storage: Vec<SlotManifest>,
+    /// Signatures of the exported procedures.
+    #[serde(default)]
+    pub procedures: Vec<ProcedureManifest>,
+    /// Directory of the manifest, used to resolve `source`.
+    #[serde(skip)]
+    dir: PathBuf,
+    /// MASM source embedded in the binary, used instead of reading `source`.
+    #[serde(skip)]
+    embedded_source: Option<&'static str>,
+    /// Cache the contract library is assembled through.
+    #[serde(skip)]
+    cache: Option<MastCache>,
+}
+
+/// A named storage slot of an [`AccountManifest`].
+#[derive(Debug, Deserialize)]
+pub struct SlotManifest {
+    /// Name of the slot.
+    pub name: String,
+    /// Type of the value, or of the map values.
+    pub value_type: Option<String>,
+    /// Type of the map keys.
+    pub key_type: Option<String>,
+    /// Kind and initial content of the slot.
+    #[serde(flatten)]
+    pub content: SlotContent,
+}
+
+/// Initial content of a storage slot.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SlotContent {
+    Value {
+        #[serde(default)]
+        value: ManifestWord,
+    },
+    Map {
+        #[serde(default)]
+        entries: Vec<MapEntryManifest>,
+    },
+}
+
+/// The signature of an exported procedure of an [`AccountManifest`]:
+///
+/// ```toml
+/// [[procedures]]
+/// name = "increment_count_of"
+/// description = "Increments the count of the given account."
+/// inputs = [{ name = "account_id", type = "account-id" }]
+/// ```
+///
+/// `inputs` and `outputs` list the operand stack from the top, typed `felt`, `u64`, `word`,
+/// `account-id` or `asset`. `advice` lists the values the procedure reads from the advice
+/// stack in order, typed `u64`, `word` or `bytes`, see [`AdviceValue`](super::AdviceValue).
+#[derive(Debug, Deserialize)]
+pub struct ProcedureManifest {
+    /// Name of the exported procedure.
+    pub name: String,
+    /// What the procedure does, copied to the generated binding.
+    pub description: Option<String>,
+    /// Where the procedure can be called from.
+    #[serde(default)]
+    pub context: ProcedureContext,
+    /// Operand stack inputs, from the top of the stack.
+    #[serde(default)]
+    pub inputs: Vec<ParamManifest>,
+    /// Operand stack outputs, from the top of the stack.
+    #[serde(default)]
+    pub outputs: Vec<ParamManifest>,
+    /// Values read from the advice stack, in order.
+    #[serde(default)]
+    pub advice: Vec<ParamManifest>,
+}
+
+/// Where a procedure can be called from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProcedureContext {
+    /// From a transaction script, the binding executes a transaction calling it.
+    #[default]
+    Transaction,
+    /// Only while a note is consumed, e.g. because it reads the note sender. No transaction
+    /// binding is generated.
+    Note,
+}
+
+/// A named and typed input, output or advice value of a [`ProcedureManifest`].
+#[derive(Debug, Deserialize)]
+pub struct ParamManifest {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub param_type: String,
+}
+
+/// An entry of a map storage slot.
+#[derive(Debug, Deserialize)]
+pub struct MapEntryManifest {
+    pub key: ManifestWord,
+    pub value: ManifestWord,
+}
+
+/// A word written either as a single number or as four numbers. A single number is stored in
+/// the last element, which is the top of the stack when the word is loaded in MASM.
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum ManifestWord {
+    Number(u64),
+    Word([u64; 4]),
+}
+
+impl Default for ManifestWord {
+    fn default() -> Self {
+        ManifestWord::Number(0)
+    }
+}
+
+impl AccountManifest {
+    /// Loads the manifest at `path`.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, ManifestError> {
+        let path = path.as_ref();
+        let contents =
+            fs::read_to_string(path).map_err(|err| ManifestError::Io(path.to_path_buf(), err))?;
+        let mut manifest = Self::parse(&contents, path)?;
+        manifest.dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+
+        Ok(manifest)
+    }
+
+    /// Parses a manifest embedded in the binary together with its MASM source. `path` is only
+    /// used in error messages.
+    pub fn from_embedded(
+        path: &str,
+        contents: &str,
+        source: &'static str,
+    ) -> Result<Self, ManifestError> {
+        let mut manifest = Self::parse(contents, Path::new(path))?;
+        manifest.embedded_source = Some(source);
+
+        Ok(manifest)
+    }
+
+    fn parse(contents: &str, path: &Path) -> Result<Self, ManifestError> {
+        Figment::from(Toml::string(contents))
+            .extract()
+            .map_err(|err| ManifestError::Malformed(path.to_path_buf(), Box::new(err)))
+    }
+
+    /// Returns the MASM source of the contract.
+    pub fn source_code(&self) -> Result<String, ManifestError> {
+        match self.embedded_source {
+            Some(source) => Ok(source.to_string()),
+            None => {
+                let source_path = self.source_path();
+                fs::read_to_string(&source_path).map_err(|err| ManifestError::Io(source_path, err))
+            }
+        }
+    }
+
+    /// Returns the path of the MASM source.
+    pub fn source_path(&self) -> PathBuf {
+        self.dir.join(&self.source)
+    }
+
+    /// Returns the module path of the contract library.
+    pub fn library_path(&self) -> String {
+        self.library
+            .clone()
+            .unwrap_or_else(|| format!("{EXTERNAL_CONTRACT_NAMESPACE}::{}_contract", self.name))
+    }
+
+    /// Compiles the MASM source as a library named [`AccountManifest::library_path`], to be
+    /// linked into transaction and note scripts that call the contract.
+    pub fn library(&self) -> Result<Library, ManifestError> {
+        let source = self.source_code()?;
+        let library = match &self.cache {
+            Some(cache) => cache.library(&self.library_path(), &source)?,
+            None => create_library(&self.library_path(), &source)?,
+        };
+
+        Ok(library)
+    }
+
+    /// Assembles the contract library through `cache`, so it is only assembled again when the
+    /// source changes.
+    pub fn with_cache(mut self, cache: MastCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Returns the index of the storage slot called `name`.
+    pub fn slot_index(&self, name: &str) -> Option<u8> {
+        self.storage
+            .iter()
+            .position(|slot| slot.name == name)
+            .map(|index| index as u8)
+    }
+
+    /// Returns the signature of the procedure called `name`, if the manifest declares one.
+    pub fn procedure(&self, name: &str) -> Option<&ProcedureManifest> {
+        self.procedures
+            .iter()
+            .find(|procedure| procedure.name == name)
+    }
+
+    /// Compiles the MASM source into an [`AccountComponent`] with the declared storage slots
+    /// and supported types.
+    pub fn component(&self) -> Result<AccountComponent, ManifestError> {
+        self.component_from_library(self.library()?)
+    }
+
+    /// Builds the [`AccountComponent`] from an already compiled contract `library`.
+    pub fn component_from_library(
+        &self,
+        library: Library,
+    ) -> Result<AccountComponent, ManifestError> {
+        let component = AccountComponent::new(library, self.storage_slots()?)?;
+        Ok(component.with_supported_types(self.supported_account_types()?))
+    }
+
+    /// Compiles the component and returns the MAST roots of its exported procedures by name.
+    pub fn procedure_roots(&self) -> Result<ProcedureRoots, ManifestError> {
+        Ok(ProcedureRoots::from_component(&self.component()?))
+    }
+
+    /// Returns the [`AccountOptions`] for an account holding the manifest's component.
+    pub fn account_options(&self) -> Result<AccountOptions, ManifestError> {
+        self.account_options_with(self.component()?)
+    }
+
+    /// Returns the [`AccountOptions`] for an account holding `component`.
+    pub(super) fn account_options_with(
+        &self,
+        component: AccountComponent,
+    ) -> Result<AccountOptions, ManifestError> {
+        if self.auth != RPO_FALCON_512_AUTH {
+            return Err(ManifestError::UnsupportedAuthScheme(self.auth.clone()));
+        }
+
+        Ok(AccountOptions::new()
+            .storage_mode(parse_storage_mode(&self.storage_mode)?)
+            .account_type(parse_account_type(&self.account_type)?)
+            .with_component(component)
+            .auth(AccountAuth::RpoFalcon512))
+    }
+
+    /// Creates a new account from the manifest. Returns the account, its seed and the secret
+    /// key of its authentication component.
+    pub fn create_account(
+        &self,
+        key_generation: KeyGeneration,
+    ) -> Result<(Account, Option<Word>, AuthSecretKey), ManifestError> {
+        let options = self.account_options()?.key_generation(key_generation);
+        Ok(create_account(options)?)
+    }
+
+    /// Returns the named and typed storage slots declared by the manifest.
+    pub fn schema(&self) -> Result<StorageSchema, ManifestError> {
+        let mut names = BTreeSet::new();
+        let slots = self
+            .storage
+            .iter()
+            .enumerate()
+            .map(|(index, slot)| {
+                if !names.insert(slot.name.as_str()) {
+                    return Err(ManifestError::DuplicateSlot(slot.name.clone()));
+                }
+                let kind = match slot.content {
+                    SlotContent::Value { .. } => SlotKind::Value,
+                    SlotContent::Map { .. } => SlotKind::Map,
+                };
+                let key_type = match (kind, &slot.key_type) {
+                    (SlotKind::Map, key_type) => Some(parse_slot_type(key_type.as_deref())?),
+                    (SlotKind::Value, None) => None,
+                    (SlotKind::Value, Some(_)) => {
+                        return Err(ManifestError::InvalidSlot {
+                            slot: slot.name.clone(),
+                            reason: "`key_type` is only allowed on map slots".to_string(),
+                        })
+                    }
+                };
+                let index = u8::try_from(index).map_err(|_| ManifestError::InvalidSlot {
+                    slot: slot.name.clone(),
+                    reason: "a component has at most 255 storage slots".to_string(),
+                })?;
+
+                Ok(SlotSchema {
+                    name: slot.name.clone(),
+                    index,
+                    kind,
+                    value_type: parse_slot_type(slot.value_type.as_deref())?,
+                    key_type,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(StorageSchema::new(slots))
+    }
+
+    /// Builds the initial storage slots, checking the initial values against the schema.
+    fn storage_slots(&self) -> Result<Vec<StorageSlot>, ManifestError> {
+        let schema = self.schema()?;
+        self.storage
+            .iter()
+            .zip(schema.slots())
+            .map(|(slot, slot_schema)| {
+                let typed_word = |word: ManifestWord, slot_type: SlotType| {
+                    let word = word.to_word(&slot.name)?;
+                    slot_type
+                        .validate(word)
+                        .map_err(|reason| ManifestError::InvalidSlot {
+                            slot: slot.name.clone(),
+                            reason,
+                        })?;
+                    Ok::<_, ManifestError>(word)
+                };
+                Ok(match &slot.content {
+                    SlotContent::Value { value } => {
+                        StorageSlot::Value(typed_word(*value, slot_schema.value_type)?)
+                    }
+                    SlotContent::Map { entries } => {
+                        let key_type = slot_schema.key_type.unwrap_or(SlotType::Word);
+                        let entries = entries
+                            .iter()
+                            .map(|entry| {
+                                Ok((
+                                    RpoDigest::from(typed_word(entry.key, key_type)?),
+                                    typed_word(entry.value, slot_schema.value_type)?,
+                                ))
+                            })
+                            .collect::<Result<Vec<_>, ManifestError>>()?;
+                        StorageSlot::Map(StorageMap::with_entries(entries)?)
+                    }
+                })
+            })
+            .collect()
+    }
+
+    fn supported_account_types(&self) -> Result<BTreeSet<AccountType>, ManifestError> {
+        if self.supported_types.iter().any(|name| name == "all") {
+            return Ok(ACCOUNT_TYPE_NAMES.iter().map(|(_, ty)| *ty).collect());
+        }
+        self.supported_types
+            .iter()
+            .map(|name| parse_account_type(name))
+            .collect()
+    }
+}
+
+impl ManifestWord {
+    fn to_word(self, slot: &str) -> Result<Word, ManifestError> {
+        let values = match self {
+            ManifestWord::Number(value) => [0, 0, 0, value],
+            ManifestWord::Word(values) => values,
+        };
+        let mut word = Word::default();
+        for (element, value) in word.iter_mut().zip(values) {
+            if value >= Felt::MODULUS {
+                return Err(ManifestError::InvalidFelt(slot.to_string(), value));
+            }
+            *element = Felt::new(value);
+        }
+
+        Ok(word)
+    }
+}
+
+fn parse_account_type(name: &str) -> Result<AccountType, ManifestError> {
+    ACCOUNT_TYPE_NAMES
+        .iter()
+        .find(|(type_name, _)| *type_name == name)
+        .map(|(_, account_type)| *account_type)
+        .ok_or_else(|| ManifestError::UnknownAccountType(name.to_string()))
+}
+
+fn parse_slot_type(name: Option<&str>) -> Result<SlotType, ManifestError> {
+    match name {
+        None => Ok(SlotType::Word),
+        Some(name) => SlotType::from_name(name)
+            .ok_or_else(|| ManifestError::UnknownSlotType(name.to_string())),
+    }
+}
+
+fn parse_storage_mode(name: &str) -> Result<AccountStorageMode, ManifestError> {
+    match name {
+        "public" => Ok(AccountStorageMode::Public),
+        "private" => Ok(AccountStorageMode::Private),
+        _ => Err(ManifestError::UnknownStorageMode(name.to_string())),
+    }
+}
+
+fn default_supported_types() -> Vec<String> {
+    vec!["all".to_string()]
+}
+
+fn default_account_type() -> String {
+    "regular-updatable".to_string()
+}
+
+fn default_storage_mode() -> String {
+    "public".to_string()
+}
+
+fn default_auth() -> String {
+    RPO_FALCON_512_AUTH.to_string()
+}
@@ -0,0 +1,169 @@
+use miden_client::{
+    accounts::AccountId,
+    assets::FungibleAsset,
+    crypto::FeltRng,
+    notes::NoteType,
+    transactions::{OutputNote, TransactionRequest, TransactionRequestError},
+    Felt,
+};
+use miden_lib::notes::create_p2id_note;
+use miden_objects::{AssetError, NoteError, MAX_OUTPUT_NOTES_PER_TX};
+
+use std::fmt;
+
+/// Errors that can occur while building a [`BatchMint`] request.
+#[derive(Debug)]
+pub enum MintError {
+    /// The batch has no notes.
+    Empty,
+    /// The batch has more notes than a transaction can create.
+    TooManyNotes(usize),
+    /// A note amount is not a valid amount of the faucet's asset.
+    Asset(AssetError),
+    /// A P2ID note could not be created.
+    Note(NoteError),
+    /// The transaction request could not be built.
+    Request(TransactionRequestError),
+}
+
+impl fmt::Display for MintError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MintError::Empty => f.write_str("batch mint has no notes"),
+            MintError::TooManyNotes(count) => write!(
+                f,
+                "batch mint has {count} notes, a transaction creates at most \
+                 {MAX_OUTPUT_NOTES_PER_TX}"
+            ),
+            MintError::Asset(err) => write!(f, "invalid mint amount: {err}"),
+            MintError::Note(err) => write!(f, "failed to create P2ID note: {err}"),
+            MintError::Request(err) => write!(f, "failed to build transaction request: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for MintError {}
+
+impl From<MintError> for String {
+    fn from(err: MintError) -> String {
+        err.to_string()
+    }
+}
+
+impl From<AssetError> for MintError {
+    fn from(err: AssetError) -> Self {
+        Self::Asset(err)
+    }
+}
+
+impl From<NoteError> for MintError {
+    fn from(err: NoteError) -> Self {
+        Self::Note(err)
+    }
+}
+
+impl From<TransactionRequestError> for MintError {
+    fn from(err: TransactionRequestError) -> Self {
+        Self::Request(err)
+    }
+}
+
+/// A P2ID note minted by a [`BatchMint`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MintNote {
+    pub target_id: AccountId,
+    pub amount: u64,
+}
+
+/// Mints several P2ID notes in a single transaction of a fungible faucet, instead of one
+/// `TransactionRequest::mint_fungible_asset` transaction per note.
+///
+/// Notes are created in the order they are added. A recipient can receive several notes, each
+/// gets its own serial number.
+///
+/// ```ignore
+/// let request = BatchMint::new(faucet_id, NoteType::Public)
+///     .note(alice_id, 100)
+///     .note(bob_id, 250)
+///     .request(client.rng())?;
+/// submit_with_retry(&mut client, faucet_id, request, RetryPolicy::default()).await?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct BatchMint {
+    faucet_id: AccountId,
+    note_type: NoteType,
+    notes: Vec<MintNote>,
+}
+
+impl BatchMint {
+    /// Starts an empty batch of notes of `note_type`, minted by the faucet `faucet_id`.
+    pub fn new(faucet_id: AccountId, note_type: NoteType) -> Self {
+        Self {
+            faucet_id,
+            note_type,
+            notes: Vec::new(),
+        }
+    }
+
+    /// Adds a note of `amount` tokens for `target_id`.
+    pub fn note(mut self, target_id: AccountId, amount: u64) -> Self {
+        self.notes.push(MintNote { target_id, amount });
+        self
+    }
+
+    /// Adds a note for each `(target_id, amount)` pair, in order.
+    pub fn notes(mut self, notes: impl IntoIterator<Item = (AccountId, u64)>) -> Self {
+        self.notes.extend(
+            notes
+                .into_iter()
+                .map(|(target_id, amount)| MintNote { target_id, amount }),
+        );
+        self
+    }
+
+    /// Returns the notes of the batch.
+    pub fn mint_notes(&self) -> &[MintNote] {
+        &self.notes
+    }
+
+    /// Returns the number of tokens minted by the batch, saturating at `u64::MAX`.
+    pub fn total_amount(&self) -> u64 {
+        self.notes
+            .iter()
+            .fold(0u64, |total, note| total.saturating_add(note.amount))
+    }
+
+    /// Returns the request minting the notes, to be executed against the faucet. Serial numbers
+    /// of the notes are drawn from `rng`, e.g. `client.rng()`.
+    ///
+    /// The created notes are the expected output notes of the request.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the batch is empty, has more notes than a transaction can create, or
+    /// a note amount exceeds the maximum amount of a fungible asset.
+    pub fn request(&self, rng: &mut impl FeltRng) -> Result<TransactionRequest, MintError> {
+        if self.notes.is_empty() {
+            return Err(MintError::Empty);
+        }
+        if self.notes.len() > MAX_OUTPUT_NOTES_PER_TX {
+            return Err(MintError::TooManyNotes(self.notes.len()));
+        }
+
+        let mut output_notes = Vec::with_capacity(self.notes.len());
+        for note in &self.notes {
+            let asset = FungibleAsset::new(self.faucet_id, note.amount)?;
+            let p2id_note = create_p2id_note(
+                self.faucet_id,
+                note.target_id,
+                vec![asset.into()],
+                self.note_type,
+                Felt::new(0),
+                rng,
+            )?;
+            output_notes.push(OutputNote::Full(p2id_note));
+        }
+
+        Ok(TransactionRequest::new().with_own_output_notes(output_notes)?)
+    }
+}
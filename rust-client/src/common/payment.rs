@@ -0,0 +1,406 @@
+use miden_client::{
+    accounts::AccountId,
+    assets::Asset,
+    crypto::RpoRandomCoin,
+    notes::{
+        Note, NoteAssets, NoteExecutionHint, NoteExecutionMode, NoteId, NoteMetadata, NoteTag,
+        NoteType,
+    },
+    store::TransactionFilter,
+    transactions::{OutputNote, TransactionRequest, TransactionRequestError, TransactionStatus},
+    Client, ClientError, Felt, Word,
+};
+use miden_lib::notes::utils::build_p2id_recipient;
+use miden_objects::{crypto::hash::rpo::Rpo256, NoteError};
+
+use std::fmt;
+
+use super::{submit_with_retry, RetryPolicy, SubmitError, SubmitErrorKind, SubmitOutcome};
+
+/// Domain separator for the serial numbers of idempotent payment notes.
+const SERIAL_NUM_DOMAIN: &[u8] = b"miden-examples/payment-serial";
+
+/// Number of blocks after its reference block a payment transaction can be committed in. A
+/// payment still uncommitted after that is known to be lost and can be sent again.
+pub const PAYMENT_EXPIRATION_DELTA: u16 = 20;
+
+/// Errors that can occur while sending an [`IdempotentPayment`].
+#[derive(Debug)]
+pub enum PaymentError {
+    /// The P2ID note of the payment could not be created.
+    Note(NoteError),
+    /// The transaction request could not be built.
+    Request(TransactionRequestError),
+    /// Looking the payment note up in the client store or on the node failed.
+    Client(Box<ClientError>),
+    /// The payment transaction could not be submitted.
+    Submit(SubmitError),
+}
+
+impl fmt::Display for PaymentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PaymentError::Note(err) => write!(f, "failed to create payment note: {err}"),
+            PaymentError::Request(err) => write!(f, "failed to build transaction request: {err}"),
+            PaymentError::Client(err) => write!(f, "failed to look up payment note: {err}"),
+            PaymentError::Submit(err) => write!(f, "failed to send payment: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for PaymentError {}
+
+impl From<PaymentError> for String {
+    fn from(err: PaymentError) -> String {
+        err.to_string()
+    }
+}
+
+impl From<NoteError> for PaymentError {
+    fn from(err: NoteError) -> Self {
+        Self::Note(err)
+    }
+}
+
+impl From<TransactionRequestError> for PaymentError {
+    fn from(err: TransactionRequestError) -> Self {
+        Self::Request(err)
+    }
+}
+
+impl From<ClientError> for PaymentError {
+    fn from(err: ClientError) -> Self {
+        Self::Client(Box::new(err))
+    }
+}
+
+impl From<SubmitError> for PaymentError {
+    fn from(err: SubmitError) -> Self {
+        Self::Submit(err)
+    }
+}
+
+/// A secret of the sender from which the serial numbers of its payment notes are derived.
+///
+/// Anyone knowing the secret can compute the serial numbers, and so the nullifiers, of the
+/// sender's payments: keep it as private as the account key. [`MasterSeed::payment_secret`]
+/// derives it from the backup phrase, so it survives a reinstall.
+///
+/// [`MasterSeed::payment_secret`]: super::MasterSeed::payment_secret
+#[derive(Clone)]
+pub struct PaymentSecret([u8; 32]);
+
+impl PaymentSecret {
+    /// Wraps an existing secret.
+    pub fn new(secret: [u8; 32]) -> Self {
+        Self(secret)
+    }
+
+    /// Derives the serial number of the payment of `sender_id` identified by
+    /// `idempotency_key`.
+    pub fn serial_num(&self, sender_id: AccountId, idempotency_key: &str) -> Word {
+        let mut input =
+            Vec::with_capacity(SERIAL_NUM_DOMAIN.len() + 32 + 8 + idempotency_key.len());
+        input.extend_from_slice(SERIAL_NUM_DOMAIN);
+        input.extend_from_slice(&self.0);
+        input.extend_from_slice(&u64::from(sender_id).to_le_bytes());
+        input.extend_from_slice(idempotency_key.as_bytes());
+
+        Rpo256::hash(&input).into()
+    }
+}
+
+impl fmt::Debug for PaymentSecret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("PaymentSecret(..)")
+    }
+}
+
+/// A P2ID payment whose note is fully determined by its fields and the sender's
+/// [`PaymentSecret`].
+///
+/// The serial number of the note is derived from the idempotency key instead of being drawn
+/// from the client RNG, so building the same payment again yields the same note ID. Retrying a
+/// payment that may already have been sent can check for that note instead of paying twice,
+/// see [`send_payment`].
+///
+/// Idempotency keys must be unique per logical payment of a sender, e.g. an invoice number:
+/// two payments with the same key, target and assets are the same note, and a transaction
+/// creating both is rejected for its duplicate output note.
+///
+/// ```ignore
+/// let secret = master_seed.payment_secret(0);
+/// let payment = IdempotentPayment::new("invoice-42", alice_id, bob_id, vec![asset.into()]);
+/// match send_payment(&mut client, &payment, &secret, RetryPolicy::default()).await? {
+///     PaymentOutcome::Sent(outcome) => println!("sent in {outcome}"),
+///     PaymentOutcome::AlreadySent(status) => println!("already sent: {status}"),
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IdempotentPayment {
+    pub idempotency_key: String,
+    pub sender_id: AccountId,
+    pub target_id: AccountId,
+    pub assets: Vec<Asset>,
+    pub note_type: NoteType,
+}
+
+impl IdempotentPayment {
+    /// Creates a public payment of `assets` from `sender_id` to `target_id`.
+    pub fn new(
+        idempotency_key: impl Into<String>,
+        sender_id: AccountId,
+        target_id: AccountId,
+        assets: Vec<Asset>,
+    ) -> Self {
+        Self {
+            idempotency_key: idempotency_key.into(),
+            sender_id,
+            target_id,
+            assets,
+            note_type: NoteType::Public,
+        }
+    }
+
+    /// Sets the type of the payment note.
+    pub fn with_note_type(mut self, note_type: NoteType) -> Self {
+        self.note_type = note_type;
+        self
+    }
+
+    /// Builds the P2ID note of the payment, the same as `create_p2id_note` except for its
+    /// serial number.
+    pub fn note(&self, secret: &PaymentSecret) -> Result<Note, PaymentError> {
+        let serial_num = secret.serial_num(self.sender_id, &self.idempotency_key);
+        let recipient = build_p2id_recipient(self.target_id, serial_num)?;
+        let tag = NoteTag::from_account_id(self.target_id, NoteExecutionMode::Local)?;
+        let metadata = NoteMetadata::new(
+            self.sender_id,
+            self.note_type,
+            tag,
+            NoteExecutionHint::always(),
+            Felt::new(0),
+        )?;
+        let assets = NoteAssets::new(self.assets.clone())?;
+
+        Ok(Note::new(assets, metadata, recipient))
+    }
+
+    /// Returns the request creating the payment note, to be executed against the sender. The
+    /// transaction expires [`PAYMENT_EXPIRATION_DELTA`] blocks after its reference block.
+    pub fn request(&self, secret: &PaymentSecret) -> Result<TransactionRequest, PaymentError> {
+        let note = self.note(secret)?;
+        Ok(TransactionRequest::new()
+            .with_own_output_notes(vec![OutputNote::Full(note)])?
+            .with_expiration_delta(PAYMENT_EXPIRATION_DELTA)?)
+    }
+}
+
+/// Whether the note of a payment was already sent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PaymentStatus {
+    /// The note is not on chain, and no transaction of the client creating it can still land.
+    Unsent,
+    /// The client submitted a transaction creating the note, which is neither committed,
+    /// discarded nor expired yet. It expires at the latest [`PAYMENT_EXPIRATION_DELTA`] blocks
+    /// after its reference block, the payment is [`PaymentStatus::Unsent`] again from then on.
+    Pending,
+    /// The note was committed in `block_num`.
+    Committed { block_num: u32 },
+}
+
+impl fmt::Display for PaymentStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PaymentStatus::Unsent => f.write_str("unsent"),
+            PaymentStatus::Pending => f.write_str("pending"),
+            PaymentStatus::Committed { block_num } => write!(f, "committed in block {block_num}"),
+        }
+    }
+}
+
+/// The result of [`send_payment`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PaymentOutcome {
+    /// A transaction creating the payment note was submitted.
+    Sent(SubmitOutcome),
+    /// The payment note was found, no transaction was submitted.
+    AlreadySent(PaymentStatus),
+}
+
+/// Returns whether the payment note `note_id` was already sent.
+///
+/// The node is asked first, so payments sent from another machine or a wiped store are found
+/// once committed. Otherwise the transactions of this client creating the note tell whether it
+/// is still pending: discarded transactions, and those the chain moved
+/// [`PAYMENT_EXPIRATION_DELTA`] blocks past without committing them, never land.
+pub async fn payment_status(
+    client: &mut Client<RpoRandomCoin>,
+    note_id: NoteId,
+) -> Result<PaymentStatus, PaymentError> {
+    // Read before asking the node, so a transaction committed up to this height is found there
+    let sync_height = client.get_sync_height().await?;
+    let committed = client
+        .rpc_api()
+        .get_notes_by_id(&[note_id])
+        .await
+        .map_err(ClientError::from)?;
+    if let Some(note) = committed.first() {
+        return Ok(PaymentStatus::Committed {
+            block_num: note.inclusion_details().block_num,
+        });
+    }
+
+    let transactions = client.get_transactions(TransactionFilter::All).await?;
+    // The store records the reference block of a transaction in `block_num`, its commit block
+    // only in `TransactionStatus::Committed`
+    Ok(local_status(
+        transactions
+            .iter()
+            .filter(|tx| tx.output_notes.iter().any(|note| note.id() == note_id))
+            .map(|tx| (&tx.transaction_status, tx.block_num)),
+        sync_height,
+    ))
+}
+
+/// Returns the status of a payment note the node has not committed, from the statuses and
+/// reference blocks of the transactions of the client creating it.
+fn local_status<'a>(
+    transactions: impl IntoIterator<Item = (&'a TransactionStatus, u32)>,
+    sync_height: u32,
+) -> PaymentStatus {
+    let mut status = PaymentStatus::Unsent;
+    for (tx_status, reference_block) in transactions {
+        match tx_status {
+            TransactionStatus::Committed(block_num) => {
+                return PaymentStatus::Committed {
+                    block_num: *block_num,
+                }
+            }
+            TransactionStatus::Pending
+                if sync_height <= reference_block + u32::from(PAYMENT_EXPIRATION_DELTA) =>
+            {
+                status = PaymentStatus::Pending;
+            }
+            TransactionStatus::Pending | TransactionStatus::Discarded => {}
+        }
+    }
+    status
+}
+
+/// Sends `payment` unless its note was already sent.
+///
+/// Calling this again for the same payment, e.g. after a crash or a timeout, never creates a
+/// second note: the derived note is looked up with [`payment_status`] first, and only sent
+/// again once an earlier transaction creating it was discarded or expired. Transactions
+/// re-executed by [`submit_with_retry`] after a stale account error create the same note too.
+///
+/// A transaction whose submission failed but which the node accepted is missing from the client
+/// store, so its payment is [`PaymentStatus::Unsent`] while in flight. Sending it again creates
+/// the same note, which the node rejects as a duplicate: that payment is returned as already
+/// sent, committed or pending.
+pub async fn send_payment(
+    client: &mut Client<RpoRandomCoin>,
+    payment: &IdempotentPayment,
+    secret: &PaymentSecret,
+    policy: RetryPolicy,
+) -> Result<PaymentOutcome, PaymentError> {
+    let note = payment.note(secret)?;
+    match payment_status(client, note.id()).await? {
+        PaymentStatus::Unsent => {}
+        status => return Ok(PaymentOutcome::AlreadySent(status)),
+    }
+
+    let request = payment.request(secret)?;
+    match submit_with_retry(client, payment.sender_id, request, policy).await {
+        Ok(outcome) => Ok(PaymentOutcome::Sent(outcome)),
+        Err(err) if err.kind == SubmitErrorKind::DuplicateNote => {
+            match payment_status(client, note.id()).await? {
+                status @ PaymentStatus::Committed { .. } => Ok(PaymentOutcome::AlreadySent(status)),
+                _ => Ok(PaymentOutcome::AlreadySent(PaymentStatus::Pending)),
+            }
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use miden_client::{
+        accounts::{AccountStorageMode, AccountType},
+        assets::FungibleAsset,
+        rpc::RpcError,
+    };
+
+    fn account(seed: u8, account_type: AccountType) -> AccountId {
+        AccountId::new_with_type_and_mode([seed; 8], account_type, AccountStorageMode::Public)
+    }
+
+    fn payment() -> IdempotentPayment {
+        let faucet_id = account(9, AccountType::FungibleFaucet);
+        IdempotentPayment::new(
+            "invoice-42",
+            account(1, AccountType::RegularAccountUpdatableCode),
+            account(2, AccountType::RegularAccountUpdatableCode),
+            vec![FungibleAsset::new(faucet_id, 100).unwrap().into()],
+        )
+    }
+
+    #[test]
+    fn pending_until_expiration() {
+        let pending = TransactionStatus::Pending;
+        let expiration = 10 + u32::from(PAYMENT_EXPIRATION_DELTA);
+
+        assert_eq!(
+            local_status([(&pending, 10)], expiration),
+            PaymentStatus::Pending
+        );
+        assert_eq!(
+            local_status([(&pending, 10)], expiration + 1),
+            PaymentStatus::Unsent
+        );
+    }
+
+    #[test]
+    fn discarded_is_unsent_and_committed_wins() {
+        let discarded = TransactionStatus::Discarded;
+        let committed = TransactionStatus::Committed(12);
+
+        assert_eq!(local_status([(&discarded, 10)], 11), PaymentStatus::Unsent);
+        assert_eq!(
+            local_status([(&discarded, 10), (&committed, 11)], 13),
+            PaymentStatus::Committed { block_num: 12 }
+        );
+    }
+
+    #[test]
+    fn resend_while_in_flight_creates_the_same_note() {
+        let secret = PaymentSecret::new([7; 32]);
+        let payment = payment();
+        let in_flight = payment.request(&secret).unwrap();
+
+        // The accepted transaction never reached the client store
+        assert_eq!(local_status([], 20), PaymentStatus::Unsent);
+
+        // Sending again creates the note of the transaction in flight, which the node rejects
+        let resent = payment.request(&secret).unwrap();
+        let note_ids = |request: &TransactionRequest| -> Vec<NoteId> {
+            request.expected_output_notes().map(Note::id).collect()
+        };
+        assert_eq!(note_ids(&resent), note_ids(&in_flight));
+        assert_eq!(note_ids(&resent), vec![payment.note(&secret).unwrap().id()]);
+
+        let rejection = ClientError::RpcError(RpcError::RequestError(
+            "SubmitProvenTransaction".to_string(),
+            "status: InvalidArgument, message: \"Output note IDs already used: [..]\", \
+             details: [], metadata: MetadataMap { headers: {} }"
+                .to_string(),
+        ));
+        assert_eq!(
+            SubmitErrorKind::classify(&rejection),
+            SubmitErrorKind::DuplicateNote
+        );
+    }
+}
@@ -0,0 +1,98 @@
+use miden_client::transactions::TransactionKernel;
+use miden_objects::{
+    accounts::AccountComponent,
+    assembly::{Assembler, Library},
+    Digest,
+};
+
+use std::{collections::BTreeMap, fmt};
+
+/// Errors that can occur while resolving procedures by name.
+#[derive(Debug)]
+pub enum ProcedureError {
+    /// The account code could not be compiled into a library.
+    Assembly(String),
+    /// The library does not export a procedure with the requested name.
+    NotFound {
+        name: String,
+        available: Vec<String>,
+    },
+}
+
+impl fmt::Display for ProcedureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProcedureError::Assembly(err) => write!(f, "failed to compile account code: {err}"),
+            ProcedureError::NotFound { name, available } => write!(
+                f,
+                "procedure `{name}` is not exported, available procedures: {}",
+                available.join(", ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ProcedureError {}
+
+impl From<ProcedureError> for String {
+    fn from(err: ProcedureError) -> String {
+        err.to_string()
+    }
+}
+
+/// The MAST roots of the procedures a library exports, keyed by procedure name.
+///
+/// These are the roots account procedures are called by, e.g. `call.<root>` in a transaction
+/// script, and the ones listed by `AccountCode::procedure_roots`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProcedureRoots(BTreeMap<String, Digest>);
+
+impl ProcedureRoots {
+    /// Compiles `account_code` as a library with the transaction kernel assembler and returns
+    /// the roots of its exported procedures.
+    pub fn compile(account_code: &str) -> Result<Self, ProcedureError> {
+        let assembler: Assembler = TransactionKernel::assembler().with_debug_mode(true);
+        let library = assembler
+            .assemble_library([account_code])
+            .map_err(|report| ProcedureError::Assembly(report.to_string()))?;
+
+        Ok(Self::from_library(&library))
+    }
+
+    /// Returns the roots of the procedures exported by `library`.
+    pub fn from_library(library: &Library) -> Self {
+        let roots = library
+            .exports()
+            .map(|export| {
+                let node_id = library.get_export_node_id(export);
+                (
+                    export.name.to_string(),
+                    library.mast_forest()[node_id].digest(),
+                )
+            })
+            .collect();
+
+        Self(roots)
+    }
+
+    /// Returns the roots of the procedures exported by `component`.
+    pub fn from_component(component: &AccountComponent) -> Self {
+        Self::from_library(component.library())
+    }
+
+    /// Returns the root of the procedure called `name`.
+    pub fn get(&self, name: &str) -> Result<Digest, ProcedureError> {
+        self.0
+            .get(name)
+            .copied()
+            .ok_or_else(|| ProcedureError::NotFound {
+                name: name.to_string(),
+                available: self.0.keys().cloned().collect(),
+            })
+    }
+
+    /// Returns the names and roots of all exported procedures, ordered by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Digest)> {
+        self.0.iter().map(|(name, root)| (name.as_str(), root))
+    }
+}
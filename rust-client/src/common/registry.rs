@@ -0,0 +1,134 @@
+//! MASM under `masm/`, embedded and assembled at build time by `build.rs`.
+//!
+//! Each file is exposed as a constant named after it, e.g. [`accounts::COUNTER`] for
+//! `masm/accounts/counter.toml`, [`scripts::COUNTER_SCRIPT`] for
+//! `masm/scripts/counter_script.masm` and [`notes::COUNTER_NOTE`] for
+//! `masm/notes/counter_note.masm`. Scripts are linked against every contract library, so they
+//! can `use` any contract under `masm/accounts`.
+
+use miden_client::{
+    accounts::Account, transactions::TransactionScript, utils::Deserializable, Felt, Word,
+};
+use miden_objects::{
+    accounts::{AccountComponent, AuthSecretKey},
+    assembly::Library,
+    notes::NoteScript,
+    vm::Program,
+};
+
+use super::{
+    create_account, AccountManifest, CallError, KeyGeneration, ManifestError, ProcedureCall,
+    ProcedureRoots,
+};
+
+include!(concat!(env!("OUT_DIR"), "/masm_registry.rs"));
+
+/// An account contract described by a manifest under `masm/accounts`.
+#[derive(Clone, Copy, Debug)]
+pub struct ContractArtifact {
+    /// Name of the contract, as declared in its manifest.
+    pub name: &'static str,
+    /// Module path scripts `use` to call the contract.
+    pub library_path: &'static str,
+    /// MASM source of the contract.
+    pub source: &'static str,
+    manifest_toml: &'static str,
+    library_bytes: &'static [u8],
+}
+
+impl ContractArtifact {
+    /// Returns the contract library compiled at build time.
+    pub fn library(&self) -> Library {
+        Library::read_from_bytes(self.library_bytes)
+            .expect("contract library is serialized by the build script")
+    }
+
+    /// Parses the embedded manifest of the contract.
+    pub fn manifest(&self) -> Result<AccountManifest, ManifestError> {
+        AccountManifest::from_embedded(
+            &format!("masm/accounts/{}.toml", self.name),
+            self.manifest_toml,
+            self.source,
+        )
+    }
+
+    /// Returns the [`AccountComponent`] built from the precompiled library and the manifest's
+    /// storage slots.
+    pub fn component(&self) -> Result<AccountComponent, ManifestError> {
+        self.manifest()?.component_from_library(self.library())
+    }
+
+    /// Returns the MAST roots of the procedures exported by the contract.
+    pub fn procedure_roots(&self) -> ProcedureRoots {
+        ProcedureRoots::from_library(&self.library())
+    }
+
+    /// Starts a transaction script calling `procedure` of the contract, see [`ProcedureCall`].
+    pub fn call(&self, procedure: &str) -> Result<ProcedureCall, CallError> {
+        ProcedureCall::new(*self, procedure)
+    }
+
+    /// Creates a new account holding the contract. Returns the account, its seed and the secret
+    /// key of its authentication component.
+    pub fn create_account(
+        &self,
+        key_generation: KeyGeneration,
+    ) -> Result<(Account, Option<Word>, AuthSecretKey), ManifestError> {
+        let manifest = self.manifest()?;
+        let component = manifest.component_from_library(self.library())?;
+        let options = manifest
+            .account_options_with(component)?
+            .key_generation(key_generation);
+
+        Ok(create_account(options)?)
+    }
+}
+
+/// A transaction script under `masm/scripts`.
+#[derive(Clone, Copy, Debug)]
+pub struct TxScriptArtifact {
+    /// File name of the script, without extension.
+    pub name: &'static str,
+    /// MASM source of the script.
+    pub source: &'static str,
+    program_bytes: &'static [u8],
+}
+
+impl TxScriptArtifact {
+    /// Returns the program compiled at build time.
+    pub fn program(&self) -> Program {
+        Program::read_from_bytes(self.program_bytes)
+            .expect("script program is serialized by the build script")
+    }
+
+    /// Returns the [`TransactionScript`] with the given script inputs.
+    pub fn tx_script<T>(&self, inputs: T) -> TransactionScript
+    where
+        T: IntoIterator<Item = (Word, Vec<Felt>)>,
+    {
+        TransactionScript::new(self.program(), inputs)
+    }
+}
+
+/// A note script under `masm/notes`.
+#[derive(Clone, Copy, Debug)]
+pub struct NoteScriptArtifact {
+    /// File name of the script, without extension.
+    pub name: &'static str,
+    /// MASM source of the script.
+    pub source: &'static str,
+    program_bytes: &'static [u8],
+}
+
+impl NoteScriptArtifact {
+    /// Returns the program compiled at build time.
+    pub fn program(&self) -> Program {
+        Program::read_from_bytes(self.program_bytes)
+            .expect("note script program is serialized by the build script")
+    }
+
+    /// Returns the [`NoteScript`].
+    pub fn note_script(&self) -> NoteScript {
+        NoteScript::new(self.program())
+    }
+}
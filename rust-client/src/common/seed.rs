@@ -0,0 +1,113 @@
+use miden_client::accounts::Account;
+use miden_objects::{
+    accounts::{AccountComponent, AuthSecretKey},
+    crypto::hash::rpo::Rpo256,
+    Word,
+};
+
+use bip39::Mnemonic;
+use rand::{rngs::OsRng, RngCore};
+use std::fmt;
+
+use super::{create_new_account_with_keys, get_pk_and_authenticator, KeyGeneration, PaymentSecret};
+
+/// Domain separator for the seeds account keys and account IDs are derived from.
+const ACCOUNT_DERIVATION_DOMAIN: &[u8] = b"miden-examples/account";
+
+/// Domain separator for the payment secrets of derived accounts.
+const PAYMENT_DERIVATION_DOMAIN: &[u8] = b"miden-examples/payment";
+
+/// Entropy of newly generated backup phrases, 32 bytes give a 24-word phrase.
+const MNEMONIC_ENTROPY_BYTES: usize = 32;
+
+/// Errors that can occur while restoring a [`MasterSeed`].
+#[derive(Debug)]
+pub enum MasterSeedError {
+    /// The backup phrase is not a valid BIP-39 mnemonic.
+    InvalidMnemonic(bip39::Error),
+}
+
+impl fmt::Display for MasterSeedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MasterSeedError::InvalidMnemonic(err) => write!(f, "invalid backup phrase: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for MasterSeedError {}
+
+/// A secret seed, restorable from a BIP-39 backup phrase, from which all accounts of a team
+/// member are derived by index.
+///
+/// The same phrase (and passphrase) always yields the same Falcon keys and account seeds, so
+/// [`MasterSeed::create_account`] reproduces the same account IDs on any machine as long as
+/// the same component is used.
+#[derive(Clone)]
+pub struct MasterSeed([u8; 64]);
+
+impl MasterSeed {
+    /// Generates a new master seed from OS entropy and returns it together with its 24-word
+    /// backup phrase.
+    pub fn generate(passphrase: &str) -> (Self, Mnemonic) {
+        let mut entropy = [0_u8; MNEMONIC_ENTROPY_BYTES];
+        OsRng.fill_bytes(&mut entropy);
+        let mnemonic =
+            Mnemonic::from_entropy(&entropy).expect("32 bytes is a valid mnemonic entropy length");
+
+        (Self(mnemonic.to_seed(passphrase)), mnemonic)
+    }
+
+    /// Restores the master seed from a backup phrase and optional passphrase (use `""` for
+    /// none).
+    pub fn from_phrase(phrase: &str, passphrase: &str) -> Result<Self, MasterSeedError> {
+        let mnemonic = Mnemonic::parse(phrase).map_err(MasterSeedError::InvalidMnemonic)?;
+        Ok(Self(mnemonic.to_seed(passphrase)))
+    }
+
+    /// Returns the seed the key and account seed of the account at `index` are generated from.
+    pub fn account_seed(&self, index: u32) -> [u8; 32] {
+        let mut input = Vec::with_capacity(ACCOUNT_DERIVATION_DOMAIN.len() + 64 + 4);
+        input.extend_from_slice(ACCOUNT_DERIVATION_DOMAIN);
+        input.extend_from_slice(&self.0);
+        input.extend_from_slice(&index.to_le_bytes());
+
+        Rpo256::hash(&input).as_bytes()
+    }
+
+    /// Derives the Falcon key pair of the account at `index`.
+    pub fn derive_key(&self, index: u32) -> (Word, AuthSecretKey) {
+        get_pk_and_authenticator(KeyGeneration::Deterministic(self.account_seed(index)))
+    }
+
+    /// Derives the account at `index` with `account_component`, its seed and its secret key.
+    ///
+    /// The returned key is the same as the one returned by [`MasterSeed::derive_key`].
+    pub fn create_account(
+        &self,
+        index: u32,
+        account_component: AccountComponent,
+    ) -> (Account, Option<Word>, AuthSecretKey) {
+        create_new_account_with_keys(
+            account_component,
+            KeyGeneration::Deterministic(self.account_seed(index)),
+        )
+    }
+
+    /// Derives the secret the payment note serial numbers of the account at `index` are
+    /// derived from, see [`PaymentSecret`].
+    pub fn payment_secret(&self, index: u32) -> PaymentSecret {
+        let mut input = Vec::with_capacity(PAYMENT_DERIVATION_DOMAIN.len() + 64 + 4);
+        input.extend_from_slice(PAYMENT_DERIVATION_DOMAIN);
+        input.extend_from_slice(&self.0);
+        input.extend_from_slice(&index.to_le_bytes());
+
+        PaymentSecret::new(Rpo256::hash(&input).as_bytes())
+    }
+}
+
+impl fmt::Debug for MasterSeed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("MasterSeed(..)")
+    }
+}
@@ -0,0 +1,371 @@
+use miden_client::{
+    accounts::{AccountId, AccountStorage, AccountType},
+    crypto::RpoRandomCoin,
+    Client, ClientError, Felt, Word,
+};
+use miden_objects::AccountError;
+
+use std::fmt;
+
+/// Errors that can occur while reading storage through a [`StorageSchema`].
+#[derive(Debug)]
+pub enum StorageError {
+    /// The schema has no slot with this name.
+    UnknownSlot {
+        name: String,
+        available: Vec<String>,
+    },
+    /// The slot is a value slot read as a map, or the other way around.
+    WrongKind { slot: String, kind: SlotKind },
+    /// The slot is read as a different type than the one it is declared with.
+    TypeMismatch {
+        slot: String,
+        declared: SlotType,
+        requested: SlotType,
+    },
+    /// The stored word is not a valid value of the declared type.
+    Decode { slot: String, reason: String },
+    /// The account storage could not be read.
+    Account(AccountError),
+    /// The account could not be synced or loaded from the client.
+    Client(Box<ClientError>),
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::UnknownSlot { name, available } => write!(
+                f,
+                "unknown storage slot `{name}`, available slots: {}",
+                available.join(", ")
+            ),
+            StorageError::WrongKind { slot, kind } => {
+                write!(f, "storage slot `{slot}` is a {kind} slot")
+            }
+            StorageError::TypeMismatch {
+                slot,
+                declared,
+                requested,
+            } => write!(
+                f,
+                "storage slot `{slot}` holds {declared} values, not {requested}"
+            ),
+            StorageError::Decode { slot, reason } => {
+                write!(f, "invalid value in storage slot `{slot}`: {reason}")
+            }
+            StorageError::Account(err) => write!(f, "failed to read account storage: {err}"),
+            StorageError::Client(err) => write!(f, "failed to load account: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {}
+
+impl From<StorageError> for String {
+    fn from(err: StorageError) -> String {
+        err.to_string()
+    }
+}
+
+/// Whether a storage slot holds a single value or a map.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SlotKind {
+    Value,
+    Map,
+}
+
+impl fmt::Display for SlotKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SlotKind::Value => f.write_str("value"),
+            SlotKind::Map => f.write_str("map"),
+        }
+    }
+}
+
+/// Type of the values (and map keys) stored in a slot.
+///
+/// Single element types are stored in the last element of the word, which is the top of the
+/// stack after `exec.account::get_item`, with the other elements zero.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SlotType {
+    Word,
+    Felt,
+    U64,
+    AccountId,
+}
+
+impl SlotType {
+    /// Names of the types as written in manifests.
+    pub const NAMES: [(&'static str, SlotType); 4] = [
+        ("word", SlotType::Word),
+        ("felt", SlotType::Felt),
+        ("u64", SlotType::U64),
+        ("account-id", SlotType::AccountId),
+    ];
+
+    /// Returns the type called `name` in manifests.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::NAMES
+            .iter()
+            .find(|(type_name, _)| *type_name == name)
+            .map(|(_, slot_type)| *slot_type)
+    }
+
+    /// Checks that `word` is a valid value of this type.
+    pub fn validate(self, word: Word) -> Result<(), String> {
+        match self {
+            SlotType::Word => Word::from_word(word).map(|_| ()),
+            SlotType::Felt => Felt::from_word(word).map(|_| ()),
+            SlotType::U64 => u64::from_word(word).map(|_| ()),
+            SlotType::AccountId => AccountId::from_word(word).map(|_| ()),
+        }
+    }
+}
+
+impl fmt::Display for SlotType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = Self::NAMES
+            .iter()
+            .find(|(_, slot_type)| slot_type == self)
+            .map(|(name, _)| *name)
+            .expect("every slot type has a name");
+        f.write_str(name)
+    }
+}
+
+/// A Rust type that can be stored in a storage slot.
+pub trait StorageValue: Sized {
+    /// The slot type this Rust type reads and writes.
+    const SLOT_TYPE: SlotType;
+
+    /// Decodes the value from a storage word.
+    fn from_word(word: Word) -> Result<Self, String>;
+
+    /// Encodes the value as a storage word.
+    fn to_word(&self) -> Word;
+}
+
+impl StorageValue for Word {
+    const SLOT_TYPE: SlotType = SlotType::Word;
+
+    fn from_word(word: Word) -> Result<Self, String> {
+        Ok(word)
+    }
+
+    fn to_word(&self) -> Word {
+        *self
+    }
+}
+
+impl StorageValue for Felt {
+    const SLOT_TYPE: SlotType = SlotType::Felt;
+
+    fn from_word(word: Word) -> Result<Self, String> {
+        match word {
+            [a, b, c, value] if [a, b, c] == [Felt::new(0); 3] => Ok(value),
+            _ => Err(format!(
+                "expected a single element, found {:?}",
+                word.map(|felt| felt.as_int())
+            )),
+        }
+    }
+
+    fn to_word(&self) -> Word {
+        [Felt::new(0), Felt::new(0), Felt::new(0), *self]
+    }
+}
+
+impl StorageValue for u64 {
+    const SLOT_TYPE: SlotType = SlotType::U64;
+
+    fn from_word(word: Word) -> Result<Self, String> {
+        Felt::from_word(word).map(|felt| felt.as_int())
+    }
+
+    fn to_word(&self) -> Word {
+        Felt::new(*self).to_word()
+    }
+}
+
+impl StorageValue for AccountId {
+    const SLOT_TYPE: SlotType = SlotType::AccountId;
+
+    fn from_word(word: Word) -> Result<Self, String> {
+        AccountId::try_from(Felt::from_word(word)?).map_err(|err| err.to_string())
+    }
+
+    fn to_word(&self) -> Word {
+        Felt::from(*self).to_word()
+    }
+}
+
+/// A named storage slot of a [`StorageSchema`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SlotSchema {
+    pub name: String,
+    pub index: u8,
+    pub kind: SlotKind,
+    /// Type of the value, or of the map values.
+    pub value_type: SlotType,
+    /// Type of the map keys, `None` for value slots.
+    pub key_type: Option<SlotType>,
+}
+
+/// The named and typed storage slots of an account component, in slot index order.
+///
+/// Built from a manifest with [`AccountManifest::schema`](super::AccountManifest::schema) and
+/// used to read the storage of accounts holding the component by slot name.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StorageSchema {
+    slots: Vec<SlotSchema>,
+}
+
+impl StorageSchema {
+    pub fn new(slots: Vec<SlotSchema>) -> Self {
+        Self { slots }
+    }
+
+    /// Returns the slots of the schema.
+    pub fn slots(&self) -> &[SlotSchema] {
+        &self.slots
+    }
+
+    /// Returns the slot called `name`.
+    pub fn slot(&self, name: &str) -> Result<&SlotSchema, StorageError> {
+        self.slots
+            .iter()
+            .find(|slot| slot.name == name)
+            .ok_or_else(|| StorageError::UnknownSlot {
+                name: name.to_string(),
+                available: self.slots.iter().map(|slot| slot.name.clone()).collect(),
+            })
+    }
+
+    /// Returns a view reading `storage` through this schema, for regular accounts whose first
+    /// component is the one described by the schema. Use [`StorageSchema::view_at`] with
+    /// [`component_storage_offset`] for faucets.
+    pub fn view<'a>(&'a self, storage: &'a AccountStorage) -> StorageView<'a> {
+        self.view_at(storage, 0)
+    }
+
+    /// Returns a view reading `storage` through this schema, for a component whose storage
+    /// starts at slot `offset` of the account.
+    pub fn view_at<'a>(&'a self, storage: &'a AccountStorage, offset: u8) -> StorageView<'a> {
+        StorageView {
+            schema: self,
+            storage,
+            offset,
+        }
+    }
+}
+
+/// Returns the slot at which the storage of a component starts in an account of
+/// `account_type`, after `preceding_slots` slots of the components before it.
+///
+/// Faucet accounts reserve slot 0 for the faucet data, their components start at slot 1.
+pub fn component_storage_offset(account_type: AccountType, preceding_slots: u8) -> u8 {
+    let reserved = match account_type {
+        AccountType::FungibleFaucet | AccountType::NonFungibleFaucet => 1,
+        AccountType::RegularAccountImmutableCode | AccountType::RegularAccountUpdatableCode => 0,
+    };
+    reserved + preceding_slots
+}
+
+/// Reads the storage of an account by slot name, checking slot kinds and types.
+#[derive(Clone, Copy, Debug)]
+pub struct StorageView<'a> {
+    schema: &'a StorageSchema,
+    storage: &'a AccountStorage,
+    offset: u8,
+}
+
+impl StorageView<'_> {
+    /// Reads the value slot called `name`.
+    pub fn get<T: StorageValue>(&self, name: &str) -> Result<T, StorageError> {
+        let slot = self.typed_slot::<T>(name, SlotKind::Value)?;
+        let word: Word = self
+            .storage
+            .get_item(self.offset.saturating_add(slot.index))
+            .map_err(StorageError::Account)?
+            .into();
+
+        decode(slot, word)
+    }
+
+    /// Reads the entry at `key` of the map slot called `name`.
+    pub fn get_map<K: StorageValue, T: StorageValue>(
+        &self,
+        name: &str,
+        key: K,
+    ) -> Result<T, StorageError> {
+        let slot = self.typed_slot::<T>(name, SlotKind::Map)?;
+        let key_type = slot.key_type.unwrap_or(SlotType::Word);
+        if key_type != K::SLOT_TYPE {
+            return Err(StorageError::TypeMismatch {
+                slot: slot.name.clone(),
+                declared: key_type,
+                requested: K::SLOT_TYPE,
+            });
+        }
+        let word = self
+            .storage
+            .get_map_item(self.offset.saturating_add(slot.index), key.to_word())
+            .map_err(StorageError::Account)?;
+
+        decode(slot, word)
+    }
+
+    fn typed_slot<T: StorageValue>(
+        &self,
+        name: &str,
+        kind: SlotKind,
+    ) -> Result<&SlotSchema, StorageError> {
+        let slot = self.schema.slot(name)?;
+        if slot.kind != kind {
+            return Err(StorageError::WrongKind {
+                slot: slot.name.clone(),
+                kind: slot.kind,
+            });
+        }
+        if slot.value_type != T::SLOT_TYPE {
+            return Err(StorageError::TypeMismatch {
+                slot: slot.name.clone(),
+                declared: slot.value_type,
+                requested: T::SLOT_TYPE,
+            });
+        }
+
+        Ok(slot)
+    }
+}
+
+/// Syncs the client, then reads the entry at `key` of the map slot `slot` of the account,
+/// e.g. the counter of one caller in a per-caller counter contract. The component described by
+/// `schema` must be the first component of the account.
+pub async fn read_map_entry<K: StorageValue, T: StorageValue>(
+    client: &mut Client<RpoRandomCoin>,
+    account_id: AccountId,
+    schema: &StorageSchema,
+    slot: &str,
+    key: K,
+) -> Result<T, StorageError> {
+    client
+        .sync_state()
+        .await
+        .map_err(|err| StorageError::Client(Box::new(err)))?;
+    let (account, _seed) = client
+        .get_account(account_id)
+        .await
+        .map_err(|err| StorageError::Client(Box::new(err)))?;
+
+    let offset = component_storage_offset(account.account_type(), 0);
+    schema.view_at(account.storage(), offset).get_map(slot, key)
+}
+
+fn decode<T: StorageValue>(slot: &SlotSchema, word: Word) -> Result<T, StorageError> {
+    T::from_word(word).map_err(|reason| StorageError::Decode {
+        slot: slot.name.clone(),
+        reason,
+    })
+}
@@ -0,0 +1,369 @@
+use miden_client::{
+    accounts::AccountId,
+    crypto::RpoRandomCoin,
+    rpc::RpcError,
+    transactions::{TransactionId, TransactionRequest, TransactionResult},
+    Client, ClientError,
+};
+
+use std::fmt;
+use tokio::time::sleep;
+
+use super::Backoff;
+
+/// gRPC status codes of failures that may succeed when the request is sent again. `Unknown` is
+/// left out, it may be any failure of the node.
+const TRANSIENT_STATUS_CODES: [&str; 3] = ["Unavailable", "DeadlineExceeded", "ResourceExhausted"];
+
+/// gRPC status codes the node rejects a transaction with.
+const REJECTION_STATUS_CODES: [&str; 2] = ["InvalidArgument", "FailedPrecondition"];
+
+/// How a failed transaction submission should be handled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SubmitErrorKind {
+    /// The node could not be reached or was overloaded, sending the transaction again may
+    /// succeed.
+    Transient,
+    /// The transaction was executed against an account state the node no longer has, e.g.
+    /// because another transaction updated the account. It has to be executed again against the
+    /// synced state.
+    StaleState,
+    /// The transaction proof could not be generated or was rejected by the node.
+    InvalidProof,
+    /// The transaction consumes a note that was already consumed, or creates a note that
+    /// already exists.
+    DuplicateNote,
+    /// Any other failure, retrying would fail the same way.
+    Permanent,
+}
+
+impl SubmitErrorKind {
+    /// Classifies an error returned by `new_transaction` or `submit_transaction`.
+    ///
+    /// Node failures only reach the client as the text of the gRPC status, so they are
+    /// classified by its status code. Rejections are told apart by the error description of the
+    /// node, with spaces and case ignored so that both its `Display` and `Debug` forms match.
+    pub fn classify(err: &ClientError) -> Self {
+        match err {
+            ClientError::RpcError(RpcError::ConnectionError(_)) => SubmitErrorKind::Transient,
+            ClientError::RpcError(RpcError::RequestError(_, status)) => match grpc_status(status) {
+                Some((code, _)) if TRANSIENT_STATUS_CODES.contains(&code) => {
+                    SubmitErrorKind::Transient
+                }
+                Some((code, message)) if REJECTION_STATUS_CODES.contains(&code) => {
+                    let message: String = message
+                        .chars()
+                        .filter(char::is_ascii_alphanumeric)
+                        .map(|c| c.to_ascii_lowercase())
+                        .collect();
+                    if message.contains("initialhash") {
+                        SubmitErrorKind::StaleState
+                    } else if message.contains("alreadyconsumed")
+                        || message.contains("alreadyused")
+                        || message.contains("alreadyexist")
+                    {
+                        SubmitErrorKind::DuplicateNote
+                    } else if message.contains("proof") {
+                        SubmitErrorKind::InvalidProof
+                    } else {
+                        SubmitErrorKind::Permanent
+                    }
+                }
+                _ => SubmitErrorKind::Permanent,
+            },
+            ClientError::TransactionProvingError(_) => SubmitErrorKind::InvalidProof,
+            _ => SubmitErrorKind::Permanent,
+        }
+    }
+
+    /// Whether the submission is attempted again.
+    pub fn is_retryable(self) -> bool {
+        matches!(
+            self,
+            SubmitErrorKind::Transient | SubmitErrorKind::StaleState
+        )
+    }
+}
+
+impl fmt::Display for SubmitErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SubmitErrorKind::Transient => f.write_str("transient node error"),
+            SubmitErrorKind::StaleState => f.write_str("stale account state"),
+            SubmitErrorKind::InvalidProof => f.write_str("invalid proof"),
+            SubmitErrorKind::DuplicateNote => f.write_str("duplicate note"),
+            SubmitErrorKind::Permanent => f.write_str("error"),
+        }
+    }
+}
+
+/// Splits the text of the `tonic::Status` of a failed request into its code and message.
+fn grpc_status(status: &str) -> Option<(&str, &str)> {
+    let (code, rest) = status.strip_prefix("status: ")?.split_once(", message: ")?;
+    let message = rest
+        .split_once(", details: ")
+        .map_or(rest, |(message, _)| message);
+    Some((code, message))
+}
+
+/// A transaction that could not be executed or submitted.
+#[derive(Debug)]
+pub struct SubmitError {
+    pub kind: SubmitErrorKind,
+    /// Number of submission attempts made.
+    pub attempts: u32,
+    pub source: Box<ClientError>,
+}
+
+impl fmt::Display for SubmitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "failed to submit transaction after {} attempt(s), {}: {}",
+            self.attempts, self.kind, self.source
+        )
+    }
+}
+
+impl std::error::Error for SubmitError {}
+
+impl SubmitError {
+    fn new(err: ClientError, attempts: u32) -> Self {
+        Self {
+            kind: SubmitErrorKind::classify(&err),
+            attempts,
+            source: Box::new(err),
+        }
+    }
+}
+
+impl From<SubmitError> for String {
+    fn from(err: SubmitError) -> String {
+        err.to_string()
+    }
+}
+
+/// How often and how fast [`submit_with_retry`] retries.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Most submission attempts, including the first one.
+    pub max_attempts: u32,
+    /// Delays between attempts.
+    pub backoff: Backoff,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            backoff: Backoff::default(),
+        }
+    }
+}
+
+/// A transaction the node accepted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SubmitOutcome {
+    /// The transaction was submitted and applied to the client store, which tracks its account
+    /// update and output notes.
+    Applied(TransactionId),
+    /// A submission of the transaction failed, yet the node committed it in `block_num`. The
+    /// client store never applied it: its output notes are not tracked, and only a sync brings
+    /// the account up to date, which fails for private accounts.
+    AcceptedNotApplied {
+        tx_id: TransactionId,
+        account_id: AccountId,
+        block_num: u32,
+    },
+}
+
+impl SubmitOutcome {
+    /// Returns the ID of the accepted transaction.
+    pub fn tx_id(&self) -> TransactionId {
+        match self {
+            SubmitOutcome::Applied(tx_id) => *tx_id,
+            SubmitOutcome::AcceptedNotApplied { tx_id, .. } => *tx_id,
+        }
+    }
+}
+
+impl From<TransactionId> for SubmitOutcome {
+    fn from(tx_id: TransactionId) -> Self {
+        Self::Applied(tx_id)
+    }
+}
+
+impl fmt::Display for SubmitOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SubmitOutcome::Applied(tx_id) => write!(f, "{tx_id}"),
+            SubmitOutcome::AcceptedNotApplied {
+                tx_id, block_num, ..
+            } => {
+                write!(
+                    f,
+                    "{tx_id} (committed in block {block_num}, not applied locally)"
+                )
+            }
+        }
+    }
+}
+
+/// Executes `request` against `account_id`, then proves and submits the transaction.
+///
+/// Transient failures are retried with the backoff of `policy`. When the node rejects the
+/// transaction because the account state it was executed against is stale, the client is
+/// synced and the request executed again against the fresh state before retrying. Other
+/// failures are returned right away, classified by [`SubmitErrorKind`].
+///
+/// A transient failure does not tell whether the node accepted the transaction, so resending
+/// it may be rejected as stale because of itself. Before executing the request again, the
+/// transactions the node committed for the account are looked up by ID: if one sent with an
+/// unknown outcome landed, [`SubmitOutcome::AcceptedNotApplied`] is returned instead of
+/// applying the request twice. A transaction still in flight is rejected as stale until it is
+/// committed, and found then.
+pub async fn submit_with_retry(
+    client: &mut Client<RpoRandomCoin>,
+    account_id: AccountId,
+    request: TransactionRequest,
+    policy: RetryPolicy,
+) -> Result<SubmitOutcome, SubmitError> {
+    // Every execution references a block at or after this one, so commits only follow it
+    let start_block = client
+        .get_sync_height()
+        .await
+        .map_err(|err| SubmitError::new(err, 0))?;
+    let mut attempts = 0;
+    let mut delay = policy.backoff.initial;
+    let mut tx_result = None;
+    // Transactions the node may have accepted despite the error
+    let mut maybe_accepted: Vec<TransactionId> = Vec::new();
+    loop {
+        attempts += 1;
+        let result = match tx_result.take() {
+            Some(tx_result) => Ok(tx_result),
+            None => client.new_transaction(account_id, request.clone()).await,
+        };
+        let err = match result {
+            Ok(executed) => {
+                let tx_id = executed.executed_transaction().id();
+                match client.submit_transaction(executed.clone()).await {
+                    Ok(()) => return Ok(SubmitOutcome::Applied(tx_id)),
+                    Err(err) => {
+                        if SubmitErrorKind::classify(&err) == SubmitErrorKind::Transient
+                            && !maybe_accepted.contains(&tx_id)
+                        {
+                            maybe_accepted.push(tx_id);
+                        }
+                        // Transient failures resubmit the same transaction
+                        tx_result = Some(executed);
+                        err
+                    }
+                }
+            }
+            Err(err) => err,
+        };
+
+        let kind = SubmitErrorKind::classify(&err);
+        if !kind.is_retryable() || attempts >= policy.max_attempts {
+            return Err(SubmitError::new(err, attempts));
+        }
+        if kind == SubmitErrorKind::StaleState {
+            tx_result = None;
+            let committed = match client.sync_state().await {
+                Ok(_) => {
+                    committed_transaction(client, account_id, start_block, &maybe_accepted).await
+                }
+                Err(err) => Err(err),
+            };
+            match committed {
+                Ok(Some(outcome)) => return Ok(outcome),
+                Ok(None) => {}
+                Err(err) => return Err(SubmitError::new(err, attempts)),
+            }
+        }
+        sleep(delay).await;
+        delay = policy.backoff.next(delay);
+    }
+}
+
+/// Returns the transaction of `candidates` the node committed for `account_id` after
+/// `block_num`, if any.
+async fn committed_transaction(
+    client: &mut Client<RpoRandomCoin>,
+    account_id: AccountId,
+    mut block_num: u32,
+    candidates: &[TransactionId],
+) -> Result<Option<SubmitOutcome>, ClientError> {
+    if candidates.is_empty() {
+        return Ok(None);
+    }
+
+    loop {
+        let info = client
+            .rpc_api()
+            .sync_state(block_num, &[account_id], &[], &[])
+            .await?;
+        if let Some(update) = info
+            .transactions
+            .iter()
+            .find(|update| candidates.contains(&update.transaction_id))
+        {
+            return Ok(Some(SubmitOutcome::AcceptedNotApplied {
+                tx_id: update.transaction_id,
+                account_id,
+                block_num: update.block_num,
+            }));
+        }
+
+        let synced_block = info.block_header.block_num();
+        if synced_block >= info.chain_tip || synced_block <= block_num {
+            return Ok(None);
+        }
+        block_num = synced_block;
+    }
+}
+
+/// Proves and submits the already executed `tx_result`, retrying transient failures with the
+/// backoff of `policy`.
+///
+/// Unlike [`submit_with_retry`], the transaction is never executed again, so the submitted
+/// transaction keeps the ID and output notes of `tx_result`. A stale account state is returned
+/// as an error, unless a submission that failed transiently was committed by the node: that
+/// resend is rejected as stale because of the transaction itself, and
+/// [`SubmitOutcome::AcceptedNotApplied`] is returned.
+pub async fn submit_executed_with_retry(
+    client: &mut Client<RpoRandomCoin>,
+    tx_result: TransactionResult,
+    policy: RetryPolicy,
+) -> Result<SubmitOutcome, SubmitError> {
+    let executed = tx_result.executed_transaction();
+    let tx_id = executed.id();
+    let account_id = executed.account_id();
+    let reference_block = executed.block_header().block_num();
+    let mut maybe_accepted = false;
+    let mut attempts = 0;
+    let mut delay = policy.backoff.initial;
+    loop {
+        attempts += 1;
+        let err = match client.submit_transaction(tx_result.clone()).await {
+            Ok(()) => return Ok(SubmitOutcome::Applied(tx_id)),
+            Err(err) => err,
+        };
+
+        let kind = SubmitErrorKind::classify(&err);
+        if kind == SubmitErrorKind::StaleState && maybe_accepted {
+            match committed_transaction(client, account_id, reference_block, &[tx_id]).await {
+                Ok(Some(outcome)) => return Ok(outcome),
+                Ok(None) => {}
+                Err(err) => return Err(SubmitError::new(err, attempts)),
+            }
+        }
+        if kind != SubmitErrorKind::Transient || attempts >= policy.max_attempts {
+            return Err(SubmitError::new(err, attempts));
+        }
+        maybe_accepted = true;
+        sleep(delay).await;
+        delay = policy.backoff.next(delay);
+    }
+}
@@ -0,0 +1,218 @@
+use miden_client::{accounts::AccountId, Felt, Word};
+use miden_objects::Digest;
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Errors that can occur while loading or rendering a [`MasmTemplate`].
+#[derive(Debug)]
+pub enum TemplateError {
+    /// The template file could not be read.
+    Io(PathBuf, io::Error),
+    /// A `{` on the given line does not start a `{name}` placeholder.
+    MalformedPlaceholder(usize),
+    /// Placeholders of the template that were not bound.
+    MissingBindings(Vec<String>),
+    /// Bindings that do not match any placeholder of the template.
+    UnusedBindings(Vec<String>),
+    /// The same placeholder was bound more than once.
+    DuplicateBinding(String),
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateError::Io(path, err) => write!(f, "failed to read {}: {err}", path.display()),
+            TemplateError::MalformedPlaceholder(line) => {
+                write!(
+                    f,
+                    "malformed placeholder on line {line}, expected `{{name}}`"
+                )
+            }
+            TemplateError::MissingBindings(names) => {
+                write!(f, "no value bound for placeholders: {}", names.join(", "))
+            }
+            TemplateError::UnusedBindings(names) => {
+                write!(f, "bindings without a placeholder: {}", names.join(", "))
+            }
+            TemplateError::DuplicateBinding(name) => {
+                write!(f, "placeholder `{name}` is bound more than once")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+impl From<TemplateError> for String {
+    fn from(err: TemplateError) -> String {
+        err.to_string()
+    }
+}
+
+/// A value substituted for a placeholder of a [`MasmTemplate`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TemplateValue {
+    /// A procedure MAST root, rendered as hex so it can follow `call.`.
+    ProcedureRoot(Digest),
+    /// A field element, rendered as a decimal number.
+    Felt(Felt),
+    /// A word, rendered as four dot-separated numbers so it can follow `push.`.
+    Word(Word),
+    /// An account ID, rendered as the decimal value of its field element.
+    AccountId(AccountId),
+}
+
+impl fmt::Display for TemplateValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateValue::ProcedureRoot(root) => f.write_str(&root.to_hex()),
+            TemplateValue::Felt(felt) => write!(f, "{}", felt.as_int()),
+            TemplateValue::Word(word) => {
+                let [a, b, c, d] = word.map(|felt| felt.as_int());
+                write!(f, "{a}.{b}.{c}.{d}")
+            }
+            TemplateValue::AccountId(account_id) => write!(f, "{}", u64::from(*account_id)),
+        }
+    }
+}
+
+impl From<Digest> for TemplateValue {
+    fn from(root: Digest) -> Self {
+        Self::ProcedureRoot(root)
+    }
+}
+
+impl From<Felt> for TemplateValue {
+    fn from(felt: Felt) -> Self {
+        Self::Felt(felt)
+    }
+}
+
+impl From<Word> for TemplateValue {
+    fn from(word: Word) -> Self {
+        Self::Word(word)
+    }
+}
+
+impl From<AccountId> for TemplateValue {
+    fn from(account_id: AccountId) -> Self {
+        Self::AccountId(account_id)
+    }
+}
+
+/// A MASM source with `{name}` placeholders, e.g. `call.{increment_count}`.
+///
+/// Placeholder names start with a letter or `_` and contain only letters, digits and `_`.
+/// Text after `#` on a line is a comment: it is neither scanned for placeholders nor
+/// substituted.
+#[derive(Clone, Debug)]
+pub struct MasmTemplate {
+    source: String,
+    placeholders: BTreeSet<String>,
+}
+
+impl MasmTemplate {
+    /// Parses `source` and collects its placeholders.
+    pub fn new(source: impl Into<String>) -> Result<Self, TemplateError> {
+        let source = source.into();
+        let mut placeholders = BTreeSet::new();
+        for (index, line) in source.lines().enumerate() {
+            let code = line.split('#').next().unwrap_or_default();
+            let mut rest = code;
+            while let Some(start) = rest.find('{') {
+                let after = &rest[start + 1..];
+                let name = after
+                    .find('}')
+                    .map(|end| &after[..end])
+                    .filter(|name| is_placeholder_name(name))
+                    .ok_or(TemplateError::MalformedPlaceholder(index + 1))?;
+                placeholders.insert(name.to_string());
+                rest = &after[name.len() + 1..];
+            }
+        }
+
+        Ok(Self {
+            source,
+            placeholders,
+        })
+    }
+
+    /// Reads and parses the template at `path`.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, TemplateError> {
+        let path = path.as_ref();
+        let source =
+            fs::read_to_string(path).map_err(|err| TemplateError::Io(path.to_path_buf(), err))?;
+        Self::new(source)
+    }
+
+    /// Returns the names of the placeholders of the template.
+    pub fn placeholders(&self) -> impl Iterator<Item = &str> {
+        self.placeholders.iter().map(String::as_str)
+    }
+
+    /// Substitutes every placeholder with its bound value and returns the resulting source.
+    /// Comments are copied as is.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a placeholder has no value, is bound twice, or a binding does not
+    /// match any placeholder.
+    pub fn render<'a, V>(
+        &self,
+        bindings: impl IntoIterator<Item = (&'a str, V)>,
+    ) -> Result<String, TemplateError>
+    where
+        V: Into<TemplateValue>,
+    {
+        let mut values = BTreeMap::new();
+        for (name, value) in bindings {
+            if values.insert(name, value.into()).is_some() {
+                return Err(TemplateError::DuplicateBinding(name.to_string()));
+            }
+        }
+
+        let unused: Vec<String> = values
+            .keys()
+            .filter(|name| !self.placeholders.contains(**name))
+            .map(|name| name.to_string())
+            .collect();
+        if !unused.is_empty() {
+            return Err(TemplateError::UnusedBindings(unused));
+        }
+        let missing: Vec<String> = self
+            .placeholders
+            .iter()
+            .filter(|name| !values.contains_key(name.as_str()))
+            .cloned()
+            .collect();
+        if !missing.is_empty() {
+            return Err(TemplateError::MissingBindings(missing));
+        }
+
+        Ok(self
+            .source
+            .split_inclusive('\n')
+            .map(|line| {
+                let (code, comment) = line
+                    .find('#')
+                    .map_or((line, ""), |start| line.split_at(start));
+                let code = values.iter().fold(code.to_string(), |code, (name, value)| {
+                    code.replace(&format!("{{{name}}}"), &value.to_string())
+                });
+                code + comment
+            })
+            .collect())
+    }
+}
+
+fn is_placeholder_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
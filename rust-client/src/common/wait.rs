@@ -0,0 +1,312 @@
+use miden_client::{
+    accounts::{Account, AccountId},
+    assets::Asset,
+    crypto::RpoRandomCoin,
+    notes::{NoteId, NoteRelevance},
+    store::{InputNoteRecord, TransactionFilter},
+    transactions::{TransactionId, TransactionStatus},
+    Client, ClientError,
+};
+
+use std::fmt;
+use tokio::time::{sleep, Duration, Instant};
+
+use super::SubmitOutcome;
+
+/// Timeout of [`wait_for_commit`] used by the examples, a few blocks of a local node.
+pub const DEFAULT_COMMIT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long to wait between two syncs while waiting for the node.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Errors that can occur while waiting for the node.
+#[derive(Debug)]
+pub enum WaitError {
+    /// The transaction was still pending when the timeout expired.
+    Timeout {
+        tx_id: TransactionId,
+        timeout: Duration,
+    },
+    /// The consumable notes of the account did not match the filter when the timeout expired.
+    NotesTimeout {
+        account_id: AccountId,
+        filter: String,
+        timeout: Duration,
+    },
+    /// The node discarded the transaction, its changes will never be committed.
+    Discarded(TransactionId),
+    /// The client does not know the transaction, e.g. because it was never submitted.
+    UnknownTransaction(TransactionId),
+    /// The node committed the transaction of a private account the client store never applied
+    /// it to, the synced account state is unknown to the client.
+    NotApplied(TransactionId),
+    /// Syncing or reading the client store failed.
+    Client(Box<ClientError>),
+}
+
+impl fmt::Display for WaitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WaitError::Timeout { tx_id, timeout } => write!(
+                f,
+                "transaction {tx_id} was not committed within {}s",
+                timeout.as_secs_f64()
+            ),
+            WaitError::NotesTimeout {
+                account_id,
+                filter,
+                timeout,
+            } => write!(
+                f,
+                "account {account_id} had no consumable notes matching {filter} within {}s",
+                timeout.as_secs_f64()
+            ),
+            WaitError::Discarded(tx_id) => write!(f, "transaction {tx_id} was discarded"),
+            WaitError::UnknownTransaction(tx_id) => {
+                write!(f, "transaction {tx_id} is not tracked by the client")
+            }
+            WaitError::NotApplied(tx_id) => write!(
+                f,
+                "transaction {tx_id} was committed but its private account was not updated locally"
+            ),
+            WaitError::Client(err) => write!(f, "failed to sync with the node: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for WaitError {}
+
+impl From<WaitError> for String {
+    fn from(err: WaitError) -> String {
+        err.to_string()
+    }
+}
+
+impl From<ClientError> for WaitError {
+    fn from(err: ClientError) -> Self {
+        Self::Client(Box::new(err))
+    }
+}
+
+/// A transaction committed to a block.
+#[derive(Clone, Debug)]
+pub struct CommittedTransaction {
+    pub tx_id: TransactionId,
+    /// Number of the block the transaction was committed in.
+    pub block_num: u32,
+    /// State of the account the transaction was executed against, synced after the commit.
+    pub account: Account,
+}
+
+/// Syncs the client until the submitted transaction is committed, and returns the block it was
+/// committed in and the synced state of its account.
+///
+/// Use this after `submit_transaction` or [`submit_with_retry`] instead of sleeping for a fixed
+/// time before reading storage: the returned account is never older than the transaction.
+/// Pass the transaction ID, or the [`SubmitOutcome`] of [`submit_with_retry`]: a transaction
+/// the node committed without the client store applying it is not tracked by the client, its
+/// public account is synced from the node instead.
+///
+/// # Errors
+///
+/// Returns an error if the transaction is discarded, is not committed within `timeout`, is not
+/// known to the client, or was committed without being applied to a private account.
+///
+/// [`submit_with_retry`]: super::submit_with_retry
+pub async fn wait_for_commit(
+    client: &mut Client<RpoRandomCoin>,
+    submitted: impl Into<SubmitOutcome>,
+    timeout: Duration,
+) -> Result<CommittedTransaction, WaitError> {
+    let tx_id = match submitted.into() {
+        SubmitOutcome::Applied(tx_id) => tx_id,
+        SubmitOutcome::AcceptedNotApplied {
+            tx_id,
+            account_id,
+            block_num,
+        } => {
+            if !account_id.is_public() {
+                return Err(WaitError::NotApplied(tx_id));
+            }
+            // The sync fetches the public account from the node, the chain is already past
+            // the block of the transaction
+            client.sync_state().await?;
+            let (account, _seed) = client.get_account(account_id).await?;
+            return Ok(CommittedTransaction {
+                tx_id,
+                block_num,
+                account,
+            });
+        }
+    };
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        client.sync_state().await?;
+        let record = client
+            .get_transactions(TransactionFilter::All)
+            .await?
+            .into_iter()
+            .find(|record| record.id == tx_id)
+            .ok_or(WaitError::UnknownTransaction(tx_id))?;
+
+        match record.transaction_status {
+            TransactionStatus::Committed(block_num) => {
+                let (account, _seed) = client.get_account(record.account_id).await?;
+                return Ok(CommittedTransaction {
+                    tx_id,
+                    block_num,
+                    account,
+                });
+            }
+            TransactionStatus::Discarded => return Err(WaitError::Discarded(tx_id)),
+            TransactionStatus::Pending => {}
+        }
+
+        let now = Instant::now();
+        if now >= deadline {
+            return Err(WaitError::Timeout { tx_id, timeout });
+        }
+        sleep(POLL_INTERVAL.min(deadline - now)).await;
+    }
+}
+
+/// Delays between polls: starting at `initial` and multiplied by `factor` after every poll, up
+/// to `max`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Backoff {
+    pub initial: Duration,
+    pub max: Duration,
+    pub factor: u32,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_millis(500),
+            max: Duration::from_secs(5),
+            factor: 2,
+        }
+    }
+}
+
+impl Backoff {
+    /// Returns the delay following `delay`.
+    pub fn next(&self, delay: Duration) -> Duration {
+        delay.saturating_mul(self.factor).min(self.max)
+    }
+}
+
+/// Which consumable notes [`wait_for_consumable_notes`] waits for.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NoteFilter {
+    /// Any `count` notes, e.g. the notes of `count` mint transactions.
+    Count(usize),
+    /// All of the given notes.
+    Ids(Vec<NoteId>),
+    /// Notes holding at least `amount` tokens of the fungible faucet `faucet_id` in total.
+    FaucetAmount { faucet_id: AccountId, amount: u64 },
+}
+
+impl NoteFilter {
+    /// Returns the notes of `notes` matching the filter, or `None` if they do not match yet.
+    pub fn select(&self, notes: &[InputNoteRecord]) -> Option<Vec<InputNoteRecord>> {
+        match self {
+            NoteFilter::Count(count) => (notes.len() >= *count).then(|| notes[..*count].to_vec()),
+            NoteFilter::Ids(ids) => ids
+                .iter()
+                .map(|id| notes.iter().find(|note| note.id() == *id).cloned())
+                .collect(),
+            NoteFilter::FaucetAmount { faucet_id, amount } => {
+                let mut selected = Vec::new();
+                let mut total = 0u64;
+                for note in notes {
+                    if total >= *amount {
+                        break;
+                    }
+                    let note_amount: u64 = note
+                        .assets()
+                        .iter()
+                        .filter_map(|asset| match asset {
+                            Asset::Fungible(asset) if asset.faucet_id() == *faucet_id => {
+                                Some(asset.amount())
+                            }
+                            _ => None,
+                        })
+                        .sum();
+                    if note_amount > 0 {
+                        total = total.saturating_add(note_amount);
+                        selected.push(note.clone());
+                    }
+                }
+                (total >= *amount).then_some(selected)
+            }
+        }
+    }
+}
+
+impl fmt::Display for NoteFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NoteFilter::Count(count) => write!(f, "{count} notes"),
+            NoteFilter::Ids(ids) => write!(f, "{} given note IDs", ids.len()),
+            NoteFilter::FaucetAmount { faucet_id, amount } => {
+                write!(f, "{amount} tokens of faucet {faucet_id}")
+            }
+        }
+    }
+}
+
+/// Syncs the client until the consumable notes of `account_id` match `filter`, polling with
+/// `backoff` delays, and returns the matching notes. Pass their IDs to
+/// `TransactionRequest::consume_notes` to consume them.
+///
+/// Only notes `account_id` can consume at the synced height count: notes that become
+/// consumable at a later block are left out until the chain reaches it.
+///
+/// # Errors
+///
+/// Returns an error if the notes do not match within `timeout`.
+pub async fn wait_for_consumable_notes(
+    client: &mut Client<RpoRandomCoin>,
+    account_id: AccountId,
+    filter: &NoteFilter,
+    timeout: Duration,
+    backoff: Backoff,
+) -> Result<Vec<InputNoteRecord>, WaitError> {
+    let deadline = Instant::now() + timeout;
+    let mut delay = backoff.initial;
+    loop {
+        client.sync_state().await?;
+        let sync_height = client.get_sync_height().await?;
+        let notes: Vec<InputNoteRecord> = client
+            .get_consumable_notes(Some(account_id))
+            .await?
+            .into_iter()
+            .filter(|(_note, consumability)| {
+                consumability.iter().any(|(consumer, relevance)| {
+                    *consumer == account_id
+                        && match relevance {
+                            NoteRelevance::Always => true,
+                            NoteRelevance::After(block_num) => *block_num <= sync_height,
+                        }
+                })
+            })
+            .map(|(note, _consumability)| note)
+            .collect();
+        if let Some(selected) = filter.select(&notes) {
+            return Ok(selected);
+        }
+
+        let now = Instant::now();
+        if now >= deadline {
+            return Err(WaitError::NotesTimeout {
+                account_id,
+                filter: filter.to_string(),
+                timeout,
+            });
+        }
+        sleep(delay.min(deadline - now)).await;
+        delay = backoff.next(delay);
+    }
+}